@@ -0,0 +1,73 @@
+// This example implements the OneMax problem: find a bit string where all bits are set,
+// using the reusable BitStringIndividual with crossover.
+
+extern crate rand;
+extern crate simplelog;
+
+// internal crates
+extern crate darwin_rs;
+
+use std::sync::Arc;
+
+use simplelog::{SimpleLogger, LogLevelFilter, Config};
+
+// internal modules
+use darwin_rs::{SimulationBuilder, Population, PopulationBuilder, simulation_builder};
+use darwin_rs::bit_string::{BitStringIndividual, one_max};
+
+const NUM_OF_BITS: usize = 100;
+
+fn make_population(count: u32) -> Vec<BitStringIndividual> {
+    let mut rng = rand::thread_rng();
+
+    (0..count).map(|_| BitStringIndividual::random(NUM_OF_BITS, Arc::new(one_max), &mut rng)).collect()
+}
+
+fn make_all_populations(individuals: u32, populations: u32) -> Vec<Population<BitStringIndividual>> {
+    let mut result = Vec::new();
+
+    for i in 1..(populations + 1) {
+        let pop = PopulationBuilder::<BitStringIndividual>::new()
+            .set_id(i)
+            .initial_population(&make_population(individuals))
+            .mutation_rate((1..4).cycle().take(individuals as usize).collect())
+            .crossover((individuals / 2) as usize)
+            .reset_limit_end(0) // disable the resetting of all individuals
+            .finalize().unwrap();
+
+        result.push(pop);
+    }
+
+    result
+}
+
+fn main() {
+    println!("Darwin test: OneMax problem");
+
+    let _ = SimpleLogger::init(LogLevelFilter::Info, Config::default());
+
+    let one_max_builder = SimulationBuilder::<BitStringIndividual>::new()
+        .fitness(0.0)
+        .threads(2)
+        .add_multiple_populations(make_all_populations(50, 4))
+        .finalize();
+
+    match one_max_builder {
+        Err(simulation_builder::Error(simulation_builder::ErrorKind::EndIterationTooLow, _)) => println!("more than 10 iteratons needed"),
+        Err(e) => println!("unexpected error: {}", e),
+        Ok(mut one_max_simulation) => {
+            one_max_simulation.run();
+
+            // A fitness of zero means all bits are set.
+            one_max_simulation.print_fitness();
+
+            if let Some(best) = one_max_simulation.best_individual() {
+                println!("best individual: {:?}", best);
+            }
+
+            println!("total run time: {} ms", one_max_simulation.total_time_in_ms);
+            println!("number of iterations: {}",
+                     one_max_simulation.simulation_result.iteration_counter);
+        }
+    }
+}
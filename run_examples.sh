@@ -12,6 +12,12 @@ cargo update
 cargo run --release
 cd ..
 
+cd onemax
+cargo clean
+cargo update
+cargo run --release
+cd ..
+
 cd queens
 cargo clean
 cargo update
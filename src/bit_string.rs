@@ -0,0 +1,203 @@
+//! This module defines a reusable individual for classic genetic algorithms where the genome
+//! is a string of bits, for example OneMax or the knapsack problem:
+//! https://en.wikipedia.org/wiki/Genetic_algorithm
+//! The fitness is calculated by a user provided function that is shared between all
+//! individuals. See also examples/onemax.
+//!
+//! darwin-rs: evolutionary algorithms with Rust
+//!
+//! Written by Willi Kappler, Version 0.4 (2017.06.26)
+//!
+//! Repository: https://github.com/willi-kappler/darwin-rs
+//!
+//! License: MIT
+//!
+//! This library allows you to write evolutionary algorithms (EA) in Rust.
+//! Examples provided: TSP, Sudoku, Queens Problem, OCR
+//!
+//!
+
+use std::fmt;
+use std::sync::Arc;
+
+use rand::{self, Rng};
+
+use individual::Individual;
+
+/// The fitness function of a `BitStringIndividual`, it gets all the bits of the individual.
+pub type FitnessFunction = Arc<dyn Fn(&[bool]) -> f64 + Send + Sync>;
+
+/// The `CrossoverMethod` type. Specifies how a child is created from two parents.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum CrossoverMethod {
+    /// Each bit is taken from one of the two parents with the same probability.
+    Uniform,
+    /// The bits before a random position are taken from the first parent, the rest from the
+    /// second parent.
+    OnePoint,
+}
+
+/// The `BitStringIndividual` type. A fixed number of bits, the fitness function is shared
+/// between all individuals.
+#[derive(Clone)]
+pub struct BitStringIndividual {
+    /// The genome of this individual.
+    pub bits: Vec<bool>,
+    /// How a child is created in the `crossover` method. Default: `CrossoverMethod::Uniform`
+    pub crossover_method: CrossoverMethod,
+    /// Calculates the fitness from the bits.
+    fitness_function: FitnessFunction,
+}
+
+impl fmt::Debug for BitStringIndividual {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bits: String = self.bits.iter().map(|bit| if *bit { '1' } else { '0' }).collect();
+        write!(f, "BitStringIndividual {{ bits: {}, crossover_method: {:?} }}", bits, self.crossover_method)
+    }
+}
+
+impl BitStringIndividual {
+    /// Creates a new individual with `len` bits that are all set to false.
+    pub fn new(len: usize, fitness_function: FitnessFunction) -> BitStringIndividual {
+        BitStringIndividual {
+            bits: vec![false; len],
+            crossover_method: CrossoverMethod::Uniform,
+            fitness_function,
+        }
+    }
+
+    /// Creates a new individual with `len` random bits.
+    pub fn random<R: Rng>(len: usize, fitness_function: FitnessFunction, rng: &mut R) -> BitStringIndividual {
+        let mut individual = BitStringIndividual::new(len, fitness_function);
+
+        for bit in &mut individual.bits {
+            *bit = rng.gen();
+        }
+
+        individual
+    }
+
+    /// Returns the number of bits that are set to true.
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().filter(|bit| **bit).count()
+    }
+
+    /// Flips one randomly chosen bit.
+    pub fn flip<R: Rng>(&mut self, rng: &mut R) {
+        if self.bits.is_empty() {
+            return;
+        }
+
+        let index = rng.gen_range(0, self.bits.len());
+        self.bits[index] = !self.bits[index];
+    }
+
+    /// Creates a new child from this individual and the other one using the `crossover_method`
+    /// of this individual. The given random number generator makes the result reproducible.
+    pub fn crossover_with<R: Rng>(&self, other: &Self, rng: &mut R) -> BitStringIndividual {
+        let mut child = self.clone();
+        let len = self.bits.len().min(other.bits.len());
+
+        match self.crossover_method {
+            CrossoverMethod::Uniform => {
+                for (bit, other_bit) in child.bits.iter_mut().zip(&other.bits) {
+                    if rng.gen() {
+                        *bit = *other_bit;
+                    }
+                }
+            }
+            CrossoverMethod::OnePoint => {
+                if len > 0 {
+                    let point = rng.gen_range(0, len + 1);
+                    child.bits[point..len].copy_from_slice(&other.bits[point..len]);
+                }
+            }
+        }
+
+        child
+    }
+}
+
+/// The fitness function for the OneMax problem: the number of bits that are not set, so zero
+/// is the perfect solution.
+pub fn one_max(bits: &[bool]) -> f64 {
+    bits.iter().filter(|bit| !**bit).count() as f64
+}
+
+impl Individual for BitStringIndividual {
+    /// Flips one randomly chosen bit.
+    fn mutate(&mut self) {
+        self.flip(&mut rand::thread_rng());
+    }
+
+    fn calculate_fitness(&mut self) -> f64 {
+        (self.fitness_function)(&self.bits)
+    }
+
+    fn reset(&mut self) {
+        for bit in &mut self.bits {
+            *bit = false;
+        }
+    }
+
+    /// The number of different bits (hamming distance).
+    fn distance(&self, other: &Self) -> f64 {
+        self.bits.iter().zip(&other.bits).filter(|&(bit1, bit2)| bit1 != bit2).count() as f64
+    }
+
+    fn genome_eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+
+    fn crossover(&self, other: &Self) -> Self {
+        self.crossover_with(other, &mut rand::thread_rng())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::{BitStringIndividual, CrossoverMethod, one_max};
+    use individual::Individual;
+    use util::seeded_rng;
+
+    #[test]
+    fn calculate_fitness1() {
+        let mut individual = BitStringIndividual::new(8, Arc::new(one_max));
+
+        assert_eq!(individual.calculate_fitness(), 8.0);
+
+        individual.bits[3] = true;
+        assert_eq!(individual.calculate_fitness(), 7.0);
+        assert_eq!(individual.count_ones(), 1);
+
+        let mut rng = seeded_rng(1);
+        individual.flip(&mut rng);
+        assert_eq!(individual.distance(&BitStringIndividual::new(8, Arc::new(one_max))), individual.count_ones() as f64);
+    }
+
+    #[test]
+    fn crossover1() {
+        let zeros = BitStringIndividual::new(16, Arc::new(one_max));
+        let mut ones = zeros.clone();
+        ones.bits = vec![true; 16];
+
+        for &crossover_method in &[CrossoverMethod::Uniform, CrossoverMethod::OnePoint] {
+            let mut parent = zeros.clone();
+            parent.crossover_method = crossover_method;
+
+            let child1 = parent.crossover_with(&ones, &mut seeded_rng(7));
+            let child2 = parent.crossover_with(&ones, &mut seeded_rng(7));
+
+            // The same seed gives the same child.
+            assert!(child1.genome_eq(&child2));
+
+            if crossover_method == CrossoverMethod::OnePoint {
+                // All ones come after the crossover point.
+                let first_one = child1.bits.iter().position(|bit| *bit).unwrap_or(16);
+                assert!(child1.bits[first_one..].iter().all(|bit| *bit));
+            }
+        }
+    }
+}
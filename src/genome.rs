@@ -0,0 +1,163 @@
+//! This module defines common mutation operators for genomes that are stored in a vector,
+//! for example a permutation of cities in the TSP case or the board in the queens case.
+//! All the operators keep the elements of the genome, they only change their order.
+//! Use these in your implementation of the `mutate` method of the `Individual` trait.
+//!
+//! darwin-rs: evolutionary algorithms with Rust
+//!
+//! Written by Willi Kappler, Version 0.4 (2017.06.26)
+//!
+//! Repository: https://github.com/willi-kappler/darwin-rs
+//!
+//! License: MIT
+//!
+//! This library allows you to write evolutionary algorithms (EA) in Rust.
+//! Examples provided: TSP, Sudoku, Queens Problem, OCR
+//!
+//!
+
+use rand::Rng;
+
+/// Returns two different random positions in the range 0..len, the first one is always the
+/// smaller one.
+fn two_positions<R: Rng>(len: usize, rng: &mut R) -> (usize, usize) {
+    let index1 = rng.gen_range(0, len);
+    let mut index2 = rng.gen_range(0, len - 1);
+
+    if index2 >= index1 {
+        index2 += 1;
+    }
+
+    if index1 < index2 { (index1, index2) } else { (index2, index1) }
+}
+
+/// Swaps two randomly chosen elements.
+pub fn swap<T, R: Rng>(genome: &mut [T], rng: &mut R) {
+    if genome.len() < 2 {
+        return;
+    }
+
+    let (index1, index2) = two_positions(genome.len(), rng);
+    genome.swap(index1, index2);
+}
+
+/// Removes a randomly chosen element and inserts it at another random position.
+/// All elements in between are shifted by one.
+pub fn insert_shift<T, R: Rng>(genome: &mut [T], rng: &mut R) {
+    if genome.len() < 2 {
+        return;
+    }
+
+    let (index1, index2) = two_positions(genome.len(), rng);
+
+    if rng.gen() {
+        genome[index1..(index2 + 1)].rotate_left(1);
+    } else {
+        genome[index1..(index2 + 1)].rotate_right(1);
+    }
+}
+
+/// Reverses the order of a randomly chosen segment.
+pub fn reverse_segment<T, R: Rng>(genome: &mut [T], rng: &mut R) {
+    if genome.len() < 2 {
+        return;
+    }
+
+    let (index1, index2) = two_positions(genome.len(), rng);
+    genome[index1..(index2 + 1)].reverse();
+}
+
+/// Shuffles all the elements of a randomly chosen segment.
+pub fn scramble_segment<T, R: Rng>(genome: &mut [T], rng: &mut R) {
+    if genome.len() < 2 {
+        return;
+    }
+
+    let (index1, index2) = two_positions(genome.len(), rng);
+    rng.shuffle(&mut genome[index1..(index2 + 1)]);
+}
+
+/// Swaps two randomly chosen blocks that don't overlap. The blocks may have different lengths.
+pub fn block_swap<T, R: Rng>(genome: &mut [T], rng: &mut R) {
+    let len = genome.len();
+
+    if len < 2 {
+        return;
+    }
+
+    // The blocks are start1..end1 and start2..end2 with start1 < end1 <= start2 < end2
+    let end1 = rng.gen_range(1, len);
+    let start1 = rng.gen_range(0, end1);
+    let end2 = rng.gen_range(end1 + 1, len + 1);
+    let start2 = rng.gen_range(end1, end2);
+
+    // [start1..end1][end1..start2][start2..end2] -> [start2..end2][end1..start2][start1..end1]
+    let segment = &mut genome[start1..end2];
+    segment.rotate_left(end1 - start1);
+    segment[..(end2 - end1)].rotate_left(start2 - end1);
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, XorShiftRng};
+
+    use super::{swap, insert_shift, reverse_segment, scramble_segment, block_swap};
+
+    fn check_operator(operator: fn(&mut [u32], &mut XorShiftRng)) {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let original: Vec<u32> = (0..20).collect();
+        let mut genome = original.clone();
+        let mut changed = false;
+
+        for _ in 0..100 {
+            operator(&mut genome, &mut rng);
+            changed |= genome != original;
+
+            let mut sorted = genome.clone();
+            sorted.sort();
+            assert_eq!(sorted, original);
+        }
+
+        assert!(changed);
+
+        // Too short genomes are not changed.
+        let mut genome = vec![7];
+        operator(&mut genome, &mut rng);
+        assert_eq!(genome, vec![7]);
+    }
+
+    #[test]
+    fn swap1() {
+        check_operator(swap);
+    }
+
+    #[test]
+    fn insert_shift1() {
+        check_operator(insert_shift);
+    }
+
+    #[test]
+    fn reverse_segment1() {
+        check_operator(reverse_segment);
+    }
+
+    #[test]
+    fn scramble_segment1() {
+        check_operator(scramble_segment);
+    }
+
+    #[test]
+    fn block_swap1() {
+        check_operator(block_swap);
+    }
+
+    #[test]
+    fn block_swap2() {
+        let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+        let mut genome = vec![1, 2];
+
+        block_swap(&mut genome, &mut rng);
+
+        assert_eq!(genome, vec![2, 1]);
+    }
+}
@@ -0,0 +1,264 @@
+//! This module defines a simple hill climber for a single individual.
+//! No populations and no threads are needed, so this is a good way to quickly test your
+//! implementation of the `Individual` trait or to compare the EA against a simple baseline.
+//!
+//! darwin-rs: evolutionary algorithms with Rust
+//!
+//! Written by Willi Kappler, Version 0.4 (2017.06.26)
+//!
+//! Repository: https://github.com/willi-kappler/darwin-rs
+//!
+//! License: MIT
+//!
+//! This library allows you to write evolutionary algorithms (EA) in Rust.
+//! Examples provided: TSP, Sudoku, Queens Problem, OCR
+//!
+//!
+
+use rand::Rng;
+
+use individual::{Individual, OptimizationGoal};
+
+/// The `AnnealingSchedule` type. Specifies the temperature of the simulated annealing in each
+/// iteration: `start_temperature * cooling_rate^iteration`.
+#[derive(Debug,Clone)]
+pub struct AnnealingSchedule {
+    /// The temperature in the first iteration. A temperature of 0.0 only accepts improvements.
+    pub start_temperature: f64,
+    /// The factor the temperature is multiplied with after each iteration, between 0.0 and 1.0.
+    pub cooling_rate: f64,
+}
+
+/// Runs a hill climber: for the given number of iterations a clone of the current individual
+/// is mutated `num_of_mutations` times. If the fitness of this clone is better than the
+/// fitness of the current individual (according to `goal`), the clone replaces the current
+/// individual. Returns the best individual found and its fitness.
+pub fn hill_climb<T: Individual + Clone>(initial: T, iterations: u32, num_of_mutations: u32,
+    goal: OptimizationGoal) -> (T, f64) {
+    let mut best = initial;
+    let mut best_fitness = best.calculate_fitness();
+
+    for _ in 0..iterations {
+        let mut candidate = best.clone();
+
+        for _ in 0..num_of_mutations {
+            candidate.mutate();
+        }
+        candidate.repair();
+
+        let fitness = candidate.calculate_fitness();
+
+        if goal.is_better(fitness, best_fitness) {
+            best = candidate;
+            best_fitness = fitness;
+        }
+    }
+
+    (best, best_fitness)
+}
+
+/// Runs a hill climber with simulated annealing (Metropolis acceptance): like `hill_climb`,
+/// but a clone with a worse fitness still replaces the current individual with the
+/// probability `exp(-distance / temperature)`, where the distance is how much worse the new
+/// fitness is (according to `goal`). This allows the search to escape from local minima.
+/// With a start temperature of 0.0 this is the same as `hill_climb`.
+/// Returns the best individual found during the whole run and its fitness.
+pub fn simulated_annealing<T: Individual + Clone, R: Rng>(initial: T, iterations: u32, num_of_mutations: u32,
+    goal: OptimizationGoal, schedule: &AnnealingSchedule, rng: &mut R) -> (T, f64) {
+    let mut current = initial;
+    let mut current_fitness = current.calculate_fitness();
+    let mut best = current.clone();
+    let mut best_fitness = current_fitness;
+    let mut temperature = schedule.start_temperature;
+
+    for _ in 0..iterations {
+        let mut candidate = current.clone();
+
+        for _ in 0..num_of_mutations {
+            candidate.mutate();
+        }
+        candidate.repair();
+
+        let fitness = candidate.calculate_fitness();
+
+        let accept = goal.is_better(fitness, current_fitness) ||
+            ((temperature > 0.0) && (rng.gen::<f64>() < (goal.improvement(current_fitness, fitness) / temperature).exp()));
+
+        if accept {
+            if goal.is_better(fitness, best_fitness) {
+                best = candidate.clone();
+                best_fitness = fitness;
+            }
+
+            current = candidate;
+            current_fitness = fitness;
+        }
+
+        temperature *= schedule.cooling_rate;
+    }
+
+    (best, best_fitness)
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+
+    use rand::{Rng, XorShiftRng};
+
+    use super::{hill_climb, simulated_annealing, AnnealingSchedule};
+    use individual::{Individual, OptimizationGoal};
+    use util::seeded_rng;
+
+    // Each test runs in its own thread and seeds its own generator with `seed_random`, so the
+    // tests don't influence each other's random numbers.
+    thread_local! {
+        static RNG: RefCell<XorShiftRng> = RefCell::new(seeded_rng(42));
+    }
+
+    fn seed_random(seed: u32) {
+        RNG.with(|rng| *rng.borrow_mut() = seeded_rng(seed));
+    }
+
+    fn next_random() -> usize {
+        RNG.with(|rng| rng.borrow_mut().gen::<u32>() as usize)
+    }
+
+    // Queens problem: one queen per column, the value is the row of the queen.
+    #[derive(Debug, Clone)]
+    struct Queens {
+        rows: Vec<usize>,
+    }
+
+    impl Individual for Queens {
+        fn mutate(&mut self) {
+            let col = next_random() % 8;
+            let row = next_random() % 8;
+            self.rows[col] = row;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            let mut num_of_collisions = 0;
+
+            for col1 in 0..8 {
+                for col2 in (col1 + 1)..8 {
+                    let row1 = self.rows[col1] as i64;
+                    let row2 = self.rows[col2] as i64;
+                    if (row1 == row2) || ((row1 - row2).abs() == (col2 - col1) as i64) {
+                        num_of_collisions += 1;
+                    }
+                }
+            }
+
+            num_of_collisions as f64
+        }
+
+        fn reset(&mut self) {
+            self.rows = vec![0; 8];
+        }
+    }
+
+    #[test]
+    fn hill_climb1() {
+        seed_random(1);
+
+        let mut initial = Queens { rows: vec![0; 8] };
+        let initial_fitness = initial.calculate_fitness();
+
+        let (mut best, best_fitness) = hill_climb(initial, 1000, 1, OptimizationGoal::Minimize);
+
+        assert_eq!(initial_fitness, 28.0);
+        assert!(best_fitness < initial_fitness);
+        assert_eq!(best.calculate_fitness(), best_fitness);
+
+        // Maximizing the number of collisions moves away from a solution.
+        let mut initial = Queens { rows: vec![0, 4, 7, 5, 2, 6, 1, 3] };
+        let initial_fitness = initial.calculate_fitness();
+
+        let (_, worst_fitness) = hill_climb(initial, 1000, 1, OptimizationGoal::Maximize);
+
+        assert_eq!(initial_fitness, 0.0);
+        assert!(worst_fitness > initial_fitness);
+    }
+
+    // A deceptive function: x = 2 is a local minimum, the global minimum is at x = 10.
+    #[derive(Debug, Clone)]
+    struct Deceptive {
+        x: i64,
+    }
+
+    impl Individual for Deceptive {
+        fn mutate(&mut self) {
+            if next_random() % 2 == 1 {
+                self.x -= 1;
+            } else {
+                self.x += 1;
+            }
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            if self.x <= 2 {
+                (4 - self.x) as f64
+            } else {
+                (10 - self.x).abs() as f64
+            }
+        }
+
+        fn reset(&mut self) {
+            self.x = 2;
+        }
+    }
+
+    #[test]
+    fn simulated_annealing1() {
+        seed_random(1);
+
+        let (best, best_fitness) = hill_climb(Deceptive { x: 2 }, 1000, 1, OptimizationGoal::Minimize);
+
+        assert_eq!(best.x, 2);
+        assert_eq!(best_fitness, 2.0);
+
+        let schedule = AnnealingSchedule { start_temperature: 10.0, cooling_rate: 0.995 };
+        let (best, best_fitness) = simulated_annealing(Deceptive { x: 2 }, 1000, 1, OptimizationGoal::Minimize, &schedule, &mut seeded_rng(1));
+
+        assert_eq!(best.x, 10);
+        assert_eq!(best_fitness, 0.0);
+
+        let schedule = AnnealingSchedule { start_temperature: 0.0, cooling_rate: 0.995 };
+        let (best, _) = simulated_annealing(Deceptive { x: 2 }, 1000, 1, OptimizationGoal::Minimize, &schedule, &mut seeded_rng(1));
+
+        assert_eq!(best.x, 2);
+    }
+
+    // The same deceptive function, but higher values are better.
+    #[derive(Debug, Clone)]
+    struct DeceptiveMax {
+        inner: Deceptive,
+    }
+
+    impl Individual for DeceptiveMax {
+        fn mutate(&mut self) {
+            self.inner.mutate();
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            -self.inner.calculate_fitness()
+        }
+
+        fn reset(&mut self) {
+            self.inner.reset();
+        }
+    }
+
+    #[test]
+    fn simulated_annealing2() {
+        seed_random(1);
+
+        let schedule = AnnealingSchedule { start_temperature: 10.0, cooling_rate: 0.995 };
+        let (best, best_fitness) = simulated_annealing(DeceptiveMax { inner: Deceptive { x: 2 } }, 1000, 1,
+            OptimizationGoal::Maximize, &schedule, &mut seeded_rng(1));
+
+        assert_eq!(best.inner.x, 10);
+        assert_eq!(best_fitness, 0.0);
+    }
+}
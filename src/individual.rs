@@ -15,6 +15,9 @@
 
 // external modules
 use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use rand::XorShiftRng;
 
 /// A wrapper helper struct for the individuals.
 /// It does the book keeping of the fitness and the number of mutations this individual
@@ -34,24 +37,84 @@ pub struct IndividualWrapper<T: Individual> {
 /// Implement this for sorting
 impl<T: Individual> PartialEq for IndividualWrapper<T> {
     fn eq(&self, other: &IndividualWrapper<T>) -> bool {
-        self.fitness == other.fitness
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 /// Implement this for sorting
 impl<T: Individual> Eq for IndividualWrapper<T> {}
 
-/// Implement this for sorting
+/// Implement this for sorting. An invalid fitness (NaN) is treated as the worst possible
+/// fitness, so these individuals are sorted to the end.
 impl<T: Individual> Ord for IndividualWrapper<T> {
     fn cmp(&self, other: &IndividualWrapper<T>) -> Ordering {
-        self.partial_cmp(other).expect("Fitness of Individual is NaN")
+        match (self.fitness.is_nan(), other.fitness.is_nan()) {
+            (false, false) => self.fitness.partial_cmp(&other.fitness).unwrap_or(Ordering::Equal),
+            (nan1, nan2) => nan1.cmp(&nan2),
+        }
     }
 }
 
 /// Implement this for sorting
 impl<T: Individual> PartialOrd for IndividualWrapper<T> {
     fn partial_cmp(&self, other: &IndividualWrapper<T>) -> Option<Ordering> {
-        self.fitness.partial_cmp(&other.fitness)
+        Some(self.cmp(other))
+    }
+}
+
+/// The `OptimizationGoal` type. Specifies if lower or higher fitness values are better.
+#[derive(Debug,Clone,Copy,PartialEq,Default)]
+pub enum OptimizationGoal {
+    /// The lower the fitness, the better. This is the default.
+    #[default]
+    Minimize,
+    /// The higher the fitness, the better.
+    Maximize,
+}
+
+impl OptimizationGoal {
+    /// Returns true if `fitness1` is strictly better than `fitness2`.
+    pub fn is_better(&self, fitness1: f64, fitness2: f64) -> bool {
+        match *self {
+            OptimizationGoal::Minimize => fitness1 < fitness2,
+            OptimizationGoal::Maximize => fitness1 > fitness2,
+        }
+    }
+
+    /// Returns by how much `new_fitness` is better than `old_fitness`.
+    /// This is negative if `new_fitness` is worse.
+    pub fn improvement(&self, old_fitness: f64, new_fitness: f64) -> f64 {
+        match *self {
+            OptimizationGoal::Minimize => old_fitness - new_fitness,
+            OptimizationGoal::Maximize => new_fitness - old_fitness,
+        }
+    }
+
+    /// Returns the ratio between the `current_fitness` and the `original_fitness`, so that the
+    /// factor gets smaller the more the fitness improves, regardless of the goal:
+    /// `current / original` for minimization and `original / current` for maximization.
+    pub fn improvement_factor(&self, original_fitness: f64, current_fitness: f64) -> f64 {
+        match *self {
+            OptimizationGoal::Minimize => current_fitness / original_fitness,
+            OptimizationGoal::Maximize => original_fitness / current_fitness,
+        }
+    }
+
+    /// Compares two individuals, the better one is ordered first.
+    /// Individuals with an invalid fitness (NaN) are always ordered last.
+    pub fn cmp<T: Individual>(&self, wrapper1: &IndividualWrapper<T>, wrapper2: &IndividualWrapper<T>) -> Ordering {
+        match *self {
+            OptimizationGoal::Minimize => wrapper1.cmp(wrapper2),
+            OptimizationGoal::Maximize if wrapper1.fitness.is_nan() || wrapper2.fitness.is_nan() => {
+                wrapper1.cmp(wrapper2)
+            }
+            OptimizationGoal::Maximize => wrapper2.cmp(wrapper1),
+        }
+    }
+
+    /// Sorts the individuals, the best one first. The sort is stable.
+    pub fn sort<T: Individual>(&self, individuals: &mut [IndividualWrapper<T>]) {
+        individuals.sort_by(|wrapper1, wrapper2| self.cmp(wrapper1, wrapper2));
     }
 }
 
@@ -75,13 +138,28 @@ pub trait Individual {
     /// order by just randomly swaping positions are very slim. So just start with one simple
     /// mutation function (one operation) and add more and more "smarter" mutation types to the
     /// mutate function.
+    ///
+    /// If the mutation has to retry a random choice until a condition holds, use
+    /// `util::retry_with_limit` to avoid an endless loop.
     fn mutate(&mut self);
     /// This method calculates the fitness for the individual. Usually this is an expensive
     /// operation and a bit more difficult to implement, compared to the mutation method above.
     /// The lower the fitness value, the better (healthier) the individual is and the closer
-    /// the individual is to the perfect solution. This can also correspont to the number of
-    /// errors like for example in the sudoku or queens problem case.
+    /// the individual is to the perfect solution (unless `OptimizationGoal::Maximize` is used).
+    /// This can also correspont to the number of errors like for example in the sudoku or
+    /// queens problem case.
+    /// If the `repair` method is implemented, this method can assume that the individual is
+    /// feasible.
     fn calculate_fitness(&mut self) -> f64;
+    /// This method calculates the fitness like `calculate_fitness`, but for a stochastic
+    /// (noisy) fitness function it should use the given random number generator instead of
+    /// `thread_rng`. The population seeds the generator from its own one and the index of the
+    /// individual, so the fitness values are reproducible with `PopulationBuilder::rng_seed`,
+    /// independent of `PopulationBuilder::eval_threads` and the scheduling of the threads.
+    /// It is optional and the default implementation just calls `calculate_fitness`.
+    fn calculate_fitness_rng(&mut self, _rng: &mut XorShiftRng) -> f64 {
+        self.calculate_fitness()
+    }
     /// This method resets each individual to an initial state.
     /// For example in the "queens" case it would reset the queens position randomly
     /// (or all in the first row).
@@ -92,11 +170,126 @@ pub trait Individual {
     fn new_fittest_found(&mut self) {
 
     }
+    /// This method is called once per iteration of the simulation on the global fittest
+    /// individual, with the best and the worst fitness of all populations. It is usefull when
+    /// you want to collect a fitness trace, for example to plot the convergence.
+    /// It is optional and the default implementation does nothing.
+    fn on_iteration(&self, _iteration: u32, _best_fitness: f64, _worst_fitness: f64) {
+
+    }
+    /// This method is called whenever a new fittest individual is found, like
+    /// `new_fittest_found`, but it also gets the top individuals of the simulation (the
+    /// "high score list", the new fittest individual first). The number of individuals is set
+    /// with `SimulationBuilder::new_best_top_n`. This is useful for example to write out the
+    /// best few solutions at once.
+    /// It is optional and the default implementation does nothing.
+    fn on_new_best_population(&self, _others: &[&Self]) where Self: Sized {
+
+    }
+    /// This method creates a new individual that lies close to this one. The `scale` parameter
+    /// specifies how far away (for example the standard deviation of a gaussian noise) the new
+    /// individual may be. It is used by the `PopulationBuilder` to create an initial population
+    /// around a user provided starting point (warm start).
+    /// It is optional and the default implementation just returns a clone.
+    fn perturb_from(&self, _scale: f64) -> Self where Self: Sized + Clone {
+        self.clone()
+    }
+    /// This method returns the values of all objectives for the individual. If the population
+    /// has weights configured, the fitness is the weighted sum of these objectives instead of
+    /// the value returned by `calculate_fitness`. Like the fitness, lower values are better
+    /// (higher values with `OptimizationGoal::Maximize`).
+    /// It is optional and the default implementation just returns the fitness as the only
+    /// objective.
+    fn objectives(&mut self) -> Vec<f64> {
+        vec![self.calculate_fitness()]
+    }
+    /// This method returns the distance between this individual and the other one.
+    /// For example this could be the number of different positions in the TSP case.
+    /// It is used to measure the diversity of a population.
+    /// It is optional and the default implementation returns 0.0 (all individuals are equal).
+    fn distance(&self, _other: &Self) -> f64 where Self: Sized {
+        0.0
+    }
+    /// This method returns true if this individual has the same genome as the other one,
+    /// for example the same positions of all queens. It is used by
+    /// `DeleteMethod::UniqueGenome` to remove duplicates without removing different
+    /// individuals that happen to have the same fitness.
+    /// It is optional and the default implementation always returns false (all individuals
+    /// are different).
+    fn genome_eq(&self, _other: &Self) -> bool where Self: Sized {
+        false
+    }
+    /// This method mutates the individual using several parents, for example by taking the
+    /// majority of the genes of all parents. The parents are the fittest individuals of the
+    /// population. It is only called if the population has `multi_parent_crossover` enabled.
+    /// It is optional and the default implementation just calls `mutate`.
+    fn mutate_with_many(&mut self, _parents: &[&Self]) where Self: Sized {
+        self.mutate();
+    }
+    /// This method creates a new child from this individual and the other one (recombination),
+    /// for example by taking the first half of the genes from this individual and the second
+    /// half from the other one. It is only called if the population has `crossover` enabled.
+    /// It is optional and the default implementation just returns a clone.
+    fn crossover(&self, _other: &Self) -> Self where Self: Sized + Clone {
+        self.clone()
+    }
+    /// This method repairs the individual after it has been mutated or created by crossover,
+    /// for example by clamping values into their allowed range or by fixing a permutation.
+    /// It is called right before the fitness is calculated, so that all constraints can be
+    /// enforced in one place instead of in every mutation operator.
+    /// It is optional and the default implementation does nothing.
+    fn repair(&mut self) {
+
+    }
+    /// This method returns by how much the individual violates the constraints of the problem,
+    /// 0.0 means that all constraints are met. If the population has a `penalty_weight`, the
+    /// weighted violation is added to the fitness as a penalty (soft constraints), in contrast
+    /// to `repair` which makes the individual feasible (hard constraints).
+    /// It is optional and the default implementation always returns 0.0.
+    fn constraint_violation(&self) -> f64 {
+        0.0
+    }
+    /// This method returns a cheap approximation of the fitness, for example calculated on a
+    /// smaller image or a subset of the data. If the population has a `prescreen_ratio`,
+    /// mutated individuals with a clearly worse approximation are discarded before the
+    /// expensive `calculate_fitness` method is called.
+    /// It is optional and the default implementation returns `None` (no approximation).
+    fn approximate_fitness(&self) -> Option<f64> {
+        None
+    }
+    /// This method returns statistics about the mutation operators of this individual, for
+    /// example how often each operator has improved the fitness. Whenever a new fittest
+    /// individual is found, the simulation adds up the statistics of the fittest individual of
+    /// each population, see `Simulation::mutation_stats`. This helps to tune the operators.
+    /// It is optional and the default implementation returns `None` (no statistics).
+    fn mutation_stats(&self) -> Option<HashMap<String, u64>> {
+        None
+    }
+    /// This method returns true if the individual (with the given fitness) is an actual
+    /// solution of the problem that can't be improved any further, for example if the sudoku
+    /// is fully valid or if there are zero collisions in the queens problem. Unlike a fitness
+    /// limit this is a property of the problem: populations with a solution as the fittest
+    /// individual stop mutating and the simulation stops as soon as the fittest individual is
+    /// a solution, regardless of the type of the simulation.
+    /// It is optional and the default implementation always returns false.
+    fn is_solution(&self, _fitness: f64) -> bool {
+        false
+    }
+    /// This method plays a game against the opponent and returns `Ordering::Greater` if this
+    /// individual wins, `Ordering::Less` if the opponent wins and `Ordering::Equal` for a draw.
+    /// It is used for problems without an absolute fitness (for example games), see the
+    /// `tournament` module.
+    /// It is optional and the default implementation always returns a draw.
+    fn play(&self, _opponent: &Self) -> Ordering where Self: Sized {
+        Ordering::Equal
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{IndividualWrapper, Individual};
+    use std::cmp::Ordering;
+
+    use super::{IndividualWrapper, Individual, OptimizationGoal};
 
     struct IndividualTest1;
 
@@ -136,4 +329,37 @@ mod test {
 
         assert!(individual1 == individual2);
     }
+
+    #[test]
+    fn compare4() {
+        let individual1 = IndividualWrapper{individual: IndividualTest1, fitness: f64::NAN, num_of_mutations: 1, id: 1};
+        let individual2 = IndividualWrapper{individual: IndividualTest1, fitness: 7.12, num_of_mutations: 2, id: 1};
+
+        assert!(individual1 > individual2);
+        assert_eq!(OptimizationGoal::Maximize.cmp(&individual1, &individual2), Ordering::Greater);
+    }
+
+    #[test]
+    fn optimization_goal1() {
+        let mut individuals = vec![
+            IndividualWrapper{individual: IndividualTest1, fitness: 3.0, num_of_mutations: 1, id: 1},
+            IndividualWrapper{individual: IndividualTest1, fitness: 1.0, num_of_mutations: 2, id: 1},
+            IndividualWrapper{individual: IndividualTest1, fitness: 3.0, num_of_mutations: 3, id: 1},
+            IndividualWrapper{individual: IndividualTest1, fitness: 2.0, num_of_mutations: 4, id: 1},
+        ];
+
+        OptimizationGoal::Minimize.sort(&mut individuals);
+        let order: Vec<u32> = individuals.iter().map(|wrapper| wrapper.num_of_mutations).collect();
+        assert_eq!(order, vec![2, 4, 1, 3]);
+
+        OptimizationGoal::Maximize.sort(&mut individuals);
+        let order: Vec<u32> = individuals.iter().map(|wrapper| wrapper.num_of_mutations).collect();
+        assert_eq!(order, vec![1, 3, 4, 2]);
+
+        assert!(OptimizationGoal::Maximize.is_better(2.0, 1.0));
+        assert!(!OptimizationGoal::Minimize.is_better(2.0, 1.0));
+        assert_eq!(OptimizationGoal::Maximize.improvement(1.0, 3.0), 2.0);
+        assert_eq!(OptimizationGoal::Maximize.improvement_factor(1.0, 4.0), 0.25);
+        assert_eq!(OptimizationGoal::Minimize.improvement_factor(4.0, 1.0), 0.25);
+    }
 }
@@ -22,15 +22,25 @@
 #[macro_use] extern crate error_chain;
 #[macro_use] extern crate log;
 extern crate jobsteal;
+extern crate rand;
 
 pub mod individual;
 pub mod simulation;
 pub mod simulation_builder;
 pub mod population;
 pub mod population_builder;
+pub mod util;
+pub mod genome;
+pub mod hill_climb;
+pub mod tournament;
+pub mod operator_selector;
+pub mod metrics;
+pub mod tsp;
+pub mod bit_string;
 
 pub use individual::Individual;
 pub use simulation::Simulation;
 pub use simulation_builder::{SimulationBuilder};
 pub use population::Population;
 pub use population_builder::{PopulationBuilder};
+pub use hill_climb::hill_climb;
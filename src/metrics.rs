@@ -0,0 +1,109 @@
+//! This module defines structured metrics of a simulation. After each iteration the simulation
+//! can pass the metrics to a user defined sink, for example to write them into a file or to
+//! send them to a monitoring system.
+//!
+//! darwin-rs: evolutionary algorithms with Rust
+//!
+//! Written by Willi Kappler, Version 0.4 (2017.06.26)
+//!
+//! Repository: https://github.com/willi-kappler/darwin-rs
+//!
+//! License: MIT
+//!
+//! This library allows you to write evolutionary algorithms (EA) in Rust.
+//! Examples provided: TSP, Sudoku, Queens Problem, OCR
+//!
+//!
+
+use std::fs::File;
+use std::io::{self, Write, BufWriter};
+use std::sync::Mutex;
+
+/// The `Metrics` type. Contains the metrics of all individuals of all populations after one
+/// iteration of the simulation.
+#[derive(Debug,Clone,PartialEq)]
+pub struct Metrics {
+    /// The current iteration, starting with 1.
+    pub iteration: u32,
+    /// The best fitness of all individuals.
+    pub best: f64,
+    /// The worst fitness of all individuals.
+    pub worst: f64,
+    /// The median fitness of all individuals.
+    pub median: f64,
+    /// The mean diversity of all populations (see `Population::diversity`).
+    pub diversity: f64,
+}
+
+/// This trait has to be implemented for a user defined metrics sink.
+/// See `SimulationBuilder::metrics_sink`.
+pub trait MetricsSink: Send {
+    /// This method is called by the simulation after each iteration.
+    fn record(&self, metrics: &Metrics);
+}
+
+/// The `JsonLinesSink` type. Writes the metrics of each iteration as one JSON object per line
+/// into a file. Non finite values (for example NaN) are written as null.
+pub struct JsonLinesSink {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl JsonLinesSink {
+    /// Creates a new sink that writes into the given file. An existing file is truncated.
+    pub fn create(path: &str) -> io::Result<JsonLinesSink> {
+        Ok(JsonLinesSink { writer: Mutex::new(BufWriter::new(File::create(path)?)) })
+    }
+}
+
+/// Formats the value as a JSON number or null.
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+impl MetricsSink for JsonLinesSink {
+    fn record(&self, metrics: &Metrics) {
+        let mut writer = match self.writer.lock() {
+            Ok(writer) => writer,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let result = writeln!(writer, "{{\"iteration\": {}, \"best\": {}, \"worst\": {}, \"median\": {}, \"diversity\": {}}}",
+            metrics.iteration, json_number(metrics.best), json_number(metrics.worst), json_number(metrics.median),
+            json_number(metrics.diversity)).and_then(|_| writer.flush());
+
+        if let Err(error) = result {
+            error!("could not write metrics: {}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Read;
+
+    use super::{Metrics, MetricsSink, JsonLinesSink};
+
+    #[test]
+    fn json_lines_sink1() {
+        let path = env::temp_dir().join("darwin_rs_json_lines_sink1.jsonl");
+
+        {
+            let sink = JsonLinesSink::create(path.to_str().unwrap()).unwrap();
+            sink.record(&Metrics { iteration: 1, best: 1.5, worst: 10.0, median: 3.0, diversity: 0.0 });
+            sink.record(&Metrics { iteration: 2, best: 1.0, worst: f64::NAN, median: 2.5, diversity: 0.25 });
+        }
+
+        let mut content = String::new();
+        File::open(&path).unwrap().read_to_string(&mut content).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(content, "{\"iteration\": 1, \"best\": 1.5, \"worst\": 10, \"median\": 3, \"diversity\": 0}\n\
+            {\"iteration\": 2, \"best\": 1, \"worst\": null, \"median\": 2.5, \"diversity\": 0.25}\n");
+    }
+}
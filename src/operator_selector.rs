@@ -0,0 +1,133 @@
+//! This module defines a helper to choose between several mutation operators inside the
+//! `mutate` method of the `Individual` trait. Instead of choosing each operator with the
+//! same probability, the operators can be weighted. The weights can also be adapted during
+//! the simulation, so that operators that improve the fitness are chosen more often.
+//!
+//! darwin-rs: evolutionary algorithms with Rust
+//!
+//! Written by Willi Kappler, Version 0.4 (2017.06.26)
+//!
+//! Repository: https://github.com/willi-kappler/darwin-rs
+//!
+//! License: MIT
+//!
+//! This library allows you to write evolutionary algorithms (EA) in Rust.
+//! Examples provided: TSP, Sudoku, Queens Problem, OCR
+//!
+//!
+
+use rand::Rng;
+
+use util::weighted_index;
+
+/// The `OperatorSelector` type. Chooses a mutation operator with a probability proportional
+/// to its weight.
+///
+/// ```
+/// extern crate rand;
+/// extern crate darwin_rs;
+///
+/// use darwin_rs::operator_selector::OperatorSelector;
+///
+/// # fn main() {
+/// // Operator 1 is chosen four times as often as operator 0.
+/// let mut selector = OperatorSelector::new(vec![1.0, 4.0]).learning_rate(0.1);
+/// let mut rng = rand::thread_rng();
+///
+/// let operator = selector.select(&mut rng);
+/// // ... apply the operator and check if the fitness has improved ...
+/// selector.update(operator, true);
+/// # }
+/// ```
+#[derive(Debug,Clone)]
+pub struct OperatorSelector {
+    /// The current weight of each operator.
+    weights: Vec<f64>,
+    /// How fast the weights adapt in `update`, between 0.0 (not at all) and 1.0.
+    learning_rate: f64,
+    /// The minimum weight of each operator, so that every operator is still chosen sometimes.
+    min_weight: f64,
+}
+
+impl OperatorSelector {
+    /// Creates a new selector with the given weights, one for each operator.
+    /// At least one weight must be given and no weight must be negative.
+    pub fn new(weights: Vec<f64>) -> OperatorSelector {
+        assert!(!weights.is_empty(), "No operator weights given");
+        assert!(weights.iter().all(|weight| *weight >= 0.0), "Negative operator weight");
+
+        OperatorSelector {
+            weights,
+            learning_rate: 0.0,
+            min_weight: 0.01,
+        }
+    }
+
+    /// Sets how fast the weights adapt to the success of the operators (see `update`).
+    /// Default: 0.0 (the weights stay fixed).
+    pub fn learning_rate(mut self, learning_rate: f64) -> OperatorSelector {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    /// Sets the minimum weight of each operator for the adaptive selection. Default: 0.01
+    pub fn min_weight(mut self, min_weight: f64) -> OperatorSelector {
+        self.min_weight = min_weight;
+        self
+    }
+
+    /// Returns the current weights of the operators.
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Returns the index of a randomly chosen operator.
+    pub fn select<R: Rng>(&self, rng: &mut R) -> usize {
+        weighted_index(rng, &self.weights)
+    }
+
+    /// Tells the selector if the given operator has improved the fitness. The weight of the
+    /// operator moves towards the mean weight (times two) if it was successful and
+    /// towards the minimum weight otherwise.
+    pub fn update(&mut self, index: usize, improved: bool) {
+        let mean = self.weights.iter().sum::<f64>() / (self.weights.len() as f64);
+        let target = if improved { 2.0 * mean } else { self.min_weight };
+        let weight = &mut self.weights[index];
+
+        *weight += self.learning_rate * (target - *weight);
+        *weight = weight.max(self.min_weight);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::OperatorSelector;
+    use util::seeded_rng;
+
+    #[test]
+    fn select1() {
+        let selector = OperatorSelector::new(vec![1.0, 0.0, 3.0]);
+        let mut rng = seeded_rng(7);
+        let mut counts = [0; 3];
+
+        for _ in 0..10000 {
+            counts[selector.select(&mut rng)] += 1;
+        }
+
+        assert_eq!(counts[1], 0);
+        assert!(counts[2] > 2 * counts[0]);
+    }
+
+    #[test]
+    fn update1() {
+        let mut selector = OperatorSelector::new(vec![1.0, 1.0]).learning_rate(0.5);
+
+        for _ in 0..10 {
+            selector.update(0, false);
+            selector.update(1, true);
+        }
+
+        assert!(selector.weights()[0] < 0.02);
+        assert!(selector.weights()[1] > 1.0);
+    }
+}
@@ -13,12 +13,287 @@
 //!
 //!
 
-use individual::{Individual, IndividualWrapper};
+use std::cmp;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use std::slice;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, Thread};
+use std::time::SystemTime;
+
+use rand::{self, Rng, XorShiftRng};
+use jobsteal::Pool;
+
+use individual::{Individual, IndividualWrapper, OptimizationGoal};
+use util::seeded_rng;
+
+/// The state shared between a population and all its `PauseHandle`s.
+#[derive(Debug,Default)]
+struct PauseState {
+    paused: AtomicBool,
+    /// The threads that are waiting in `wait_while_all_paused`.
+    waiters: Mutex<Vec<Thread>>,
+}
+
+/// The `PauseHandle` type. Allows to pause and resume a population from another thread,
+/// for example by an external scheduler. Get one with `Population::pause_handle`.
+#[derive(Debug,Clone,Default)]
+pub struct PauseHandle {
+    state: Arc<PauseState>,
+}
+
+impl PauseHandle {
+    /// Pauses the population: it will not do any work until `resume` is called.
+    pub fn pause(&self) {
+        self.state.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resumes the population after a call to `pause` and wakes up all the threads that are
+    /// waiting for it.
+    pub fn resume(&self) {
+        self.state.paused.store(false, Ordering::SeqCst);
+        self.wake();
+    }
+
+    /// Returns true if the population is currently paused.
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.load(Ordering::SeqCst)
+    }
+
+    /// Wakes up all the threads that are waiting for the population, without resuming it.
+    /// They check their condition again, see `wait_while_all_paused`.
+    pub fn wake(&self) {
+        for thread in self.state.waiters.lock().unwrap().iter() {
+            thread.unpark();
+        }
+    }
+
+    /// Blocks the current thread until the population is resumed. Returns immediately if the
+    /// population isn't paused.
+    pub fn wait_while_paused(&self) {
+        wait_while_all_paused(slice::from_ref(self), || false);
+    }
+}
+
+/// Blocks the current thread while all the populations of the given handles are paused,
+/// without using the CPU. Returns as soon as one of them is resumed or `cancel` returns true.
+/// `cancel` is checked again whenever one of the handles calls `resume` or `wake`.
+pub fn wait_while_all_paused<F: Fn() -> bool>(handles: &[PauseHandle], cancel: F) {
+    if handles.is_empty() {
+        return;
+    }
+
+    let current = thread::current();
+
+    // Register first, so that a `resume` after the check below still unparks this thread.
+    for handle in handles {
+        handle.state.waiters.lock().unwrap().push(current.clone());
+    }
+
+    while !cancel() && handles.iter().all(|handle| handle.is_paused()) {
+        thread::park();
+    }
+
+    for handle in handles {
+        handle.state.waiters.lock().unwrap().retain(|thread| thread.id() != current.id());
+    }
+}
+
+/// The `ResetMethod` type. Specifies which individuals are resetted when the reset limit or
+/// a restart is reached.
+#[derive(Debug,Clone,PartialEq)]
+pub enum ResetMethod {
+    /// Reset all individuals. This is the default.
+    All,
+    /// Reset only the worse half of the individuals.
+    WorstHalf,
+    /// Keep the fittest individual and reset all the others.
+    KeepBest,
+    /// Don't reset any individual, instead mutate each one `strength` times (warm restart).
+    /// This keeps most of the structure of the individuals while escaping the local minimum.
+    Perturb { strength: u32 },
+}
+
+/// The `RestartStrategy` type. Specifies when a population is restarted: all individuals
+/// are resetted, since the population is most likely stuck in a local minimum.
+/// The global fittest individuals of the simulation are kept across restarts.
+#[derive(Debug,Clone,PartialEq)]
+pub enum RestartStrategy {
+    /// Use the reset limit (`reset_limit_start`, `reset_limit_end`, `reset_limit_increment`).
+    None,
+    /// Restart the population every `rounds` iterations.
+    FixedInterval { rounds: u32 },
+    /// Restart the population after `base_rounds` iterations and double the number of
+    /// individuals and the number of iterations until the next restart each time.
+    /// The number of individuals grows up to `max_individuals` (it never shrinks) and the
+    /// number of iterations until the next restart is limited to `u32::MAX`.
+    Doubling { base_rounds: u32, max_individuals: u32 },
+    /// Restart the population according to the Luby sequence (1, 1, 2, 1, 1, 2, 4, ...)
+    /// multiplied by `unit` iterations.
+    Luby { unit: u32 },
+}
+
+/// The `SortMethod` type. Specifies how the individuals are ranked before the least fit ones
+/// are removed in each iteration.
+#[derive(Debug,Clone,PartialEq)]
+pub enum SortMethod {
+    /// Sort by fitness only.
+    Fitness,
+    /// Sort by pareto fronts over the `objectives` of the individuals: first all individuals
+    /// that are not dominated by any other one, then the ones that are only dominated by the
+    /// first front and so on. Inside each front the individuals are sorted by fitness.
+    Pareto,
+}
+
+/// The `DeleteMethod` type. Specifies which individuals survive after the population has
+/// been ranked in each iteration.
+#[derive(Debug,Clone,PartialEq)]
+pub enum DeleteMethod {
+    /// Keep the fittest individuals (and a reservoir of diverse ones, if `keep_worst` is set).
+    Truncate,
+    /// Always keep the `elite` fittest individuals and fill the remaining slots by randomly
+    /// choosing from all the other individuals.
+    Elitist { elite: usize },
+    /// Repeatedly choose `size` individuals randomly and keep the best of them, until the
+    /// population has its original length. This keeps more diversity than `Truncate`.
+    Tournament { size: usize },
+    /// Keep the fittest individuals, but skip individuals with the same genome as a fitter
+    /// individual (see the `genome_eq` method of the `Individual` trait). This keeps different
+    /// individuals with the same fitness. Each individual is compared against all kept ones, so
+    /// duplicates don't need to be next to each other after sorting. If there are not enough
+    /// different individuals, the remaining slots are filled with the fittest duplicates.
+    UniqueGenome,
+}
+
+/// Formats the delete method, the same format is accepted by `from_str`:
+/// "truncate", "elitist:<elite>", "tournament:<size>" or "unique_genome".
+impl fmt::Display for DeleteMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DeleteMethod::Truncate => write!(f, "truncate"),
+            DeleteMethod::Elitist { elite } => write!(f, "elitist:{}", elite),
+            DeleteMethod::Tournament { size } => write!(f, "tournament:{}", size),
+            DeleteMethod::UniqueGenome => write!(f, "unique_genome"),
+        }
+    }
+}
+
+/// Parses a delete method, for example from a command line option. See `fmt` for the format.
+impl FromStr for DeleteMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DeleteMethod, String> {
+        let mut parts = s.splitn(2, ':');
+        let name = parts.next().unwrap_or("");
+        let parameter = parts.next().map(|value| value.parse::<usize>());
+
+        match (name, parameter) {
+            ("truncate", None) => Ok(DeleteMethod::Truncate),
+            ("elitist", Some(Ok(elite))) => Ok(DeleteMethod::Elitist { elite }),
+            ("tournament", Some(Ok(size))) => Ok(DeleteMethod::Tournament { size }),
+            ("unique_genome", None) => Ok(DeleteMethod::UniqueGenome),
+            _ => Err(format!("invalid delete method: {}", s)),
+        }
+    }
+}
+
+/// Converts a numeric code to a delete method: 0 = truncate, 1 = elitist (one elite
+/// individual), 2 = tournament (size two), 3 = unique genome.
+impl TryFrom<u8> for DeleteMethod {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<DeleteMethod, String> {
+        match value {
+            0 => Ok(DeleteMethod::Truncate),
+            1 => Ok(DeleteMethod::Elitist { elite: 1 }),
+            2 => Ok(DeleteMethod::Tournament { size: 2 }),
+            3 => Ok(DeleteMethod::UniqueGenome),
+            _ => Err(format!("invalid delete method: {}", value)),
+        }
+    }
+}
+
+/// Returns true if the objectives `a` dominate the objectives `b`: `a` is not worse in any
+/// objective and better in at least one.
+fn dominates(goal: OptimizationGoal, a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(a, b)| !goal.is_better(*b, *a)) &&
+        a.iter().zip(b).any(|(a, b)| goal.is_better(*a, *b))
+}
+
+/// The `MutationSchedule` type. Specifies how many mutations an individual does in each
+/// iteration. This allows aggressive exploration at the beginning and fine tuning later on.
+#[derive(Debug,Clone,PartialEq)]
+pub enum MutationSchedule {
+    /// Use the number of mutations of each individual (see `PopulationBuilder::mutation_rate`).
+    Constant,
+    /// Decrease the number of mutations for all individuals linearly from `start` to `end`
+    /// during the given number of iterations and stay at `end` after that.
+    LinearDecay { start: u32, end: u32, iterations: u32 },
+    /// Add one mutation to the number of mutations of each individual for every iteration
+    /// without a new fittest individual in this population, up to `max` mutations.
+    /// Drop back to the original number after an improvement.
+    AdaptiveStagnation { max: u32 },
+}
+
+impl MutationSchedule {
+    /// Returns the number of mutations for an individual with the given (original) number of
+    /// mutations in the given iteration (starting with 0), when the population hasn't found
+    /// a new fittest individual for `stagnation` iterations.
+    pub fn num_of_mutations(&self, num_of_mutations: u32, iteration: u32, stagnation: u32) -> u32 {
+        match *self {
+            MutationSchedule::Constant => num_of_mutations,
+            MutationSchedule::LinearDecay { start, end, iterations } => {
+                if iteration >= iterations {
+                    end
+                } else {
+                    let fraction = f64::from(iteration) / f64::from(iterations);
+                    (f64::from(start) + ((f64::from(end) - f64::from(start)) * fraction)).round() as u32
+                }
+            }
+            MutationSchedule::AdaptiveStagnation { max } => {
+                num_of_mutations.saturating_add(stagnation).min(max)
+            }
+        }
+    }
+}
+
+/// The `ConvergenceAction` type. Specifies what happens when a population has fully
+/// converged, that means all individuals have the same fitness.
+#[derive(Debug,Clone,PartialEq)]
+pub enum ConvergenceAction {
+    /// Just log the convergence and go on.
+    Continue,
+    /// Reset all individuals of the population.
+    Restart,
+    /// Stop the simulation.
+    Stop,
+}
+
+/// Returns the ith element (starting with 1) of the Luby sequence: 1, 1, 2, 1, 1, 2, 4, ...
+fn luby(i: u32) -> u32 {
+    // Use u64 to avoid an overflow for large values of i.
+    let i = i as u64;
+    let mut k = 1;
+
+    while (1u64 << k) - 1 < i {
+        k += 1;
+    }
+
+    if (1u64 << k) - 1 == i {
+        1 << (k - 1)
+    } else {
+        luby((i - (1u64 << (k - 1)) + 1) as u32)
+    }
+}
 
 /// The `Population` type. Contains the actual individuals (through a wrapper) and informations
 /// like the `reset_limit`. Use the `PopulationBuilder` in your main program to create populations.
+/// The random number generator can be replaced with `PopulationBuilder::with_rng`, by default
+/// the fast (but not cryptographically secure) `XorShiftRng` is used.
 #[derive(Clone)]
-pub struct Population<T: Individual> {
+pub struct Population<T: Individual, R: Rng = XorShiftRng> {
     /// The number of individuals for this population.
     pub num_of_individuals: u32,
     /// The actual population (vector of individuals).
@@ -45,97 +320,2406 @@ pub struct Population<T: Individual> {
     pub id: u32,
     /// Count how often this population has created (found) the fittest individual. This may help
     /// you to fine tune the parameters for the population and the simulation in general.
-    pub fitness_counter: u64
+    pub fitness_counter: u64,
+    /// The time when this population has created (found) the fittest individual the last time.
+    /// This helps to find populations that don't contribute anymore.
+    pub last_improvement: Option<SystemTime>,
+    /// The number of less fit individuals that are kept in each iteration instead of being
+    /// discarded. These are the ones furthest away from the fittest individuals (see the
+    /// `distance` method of the `Individual` trait), or simply the worst ones if all distances
+    /// are 0.0. This keeps some genetic diversity in the population. Default: 0 (disabled).
+    pub keep_worst: usize,
+    /// The weights for the objectives of each individual. If this is not empty, the fitness
+    /// is calculated as the weighted sum of the `objectives` of each individual instead of
+    /// calling `calculate_fitness`. Default: empty (disabled).
+    pub weights: Vec<f64>,
+    /// The weight of the constraint violation of each individual (see the `constraint_violation`
+    /// method of the `Individual` trait). The violation times this weight is added to the fitness
+    /// (or subtracted if higher fitness values are better). Default: 0.0 (disabled).
+    pub penalty_weight: f64,
+    /// If set, a mutated individual is discarded without calculating its fitness if its
+    /// approximate fitness (see the `approximate_fitness` method of the `Individual` trait) is
+    /// worse than the fitness before the mutation by more than this ratio of the absolute
+    /// fitness. Default: None (disabled).
+    pub prescreen_ratio: Option<f64>,
+    /// The number of threads that calculate the fitness of the mutated individuals.
+    /// Default: 1 (no extra threads).
+    pub eval_threads: usize,
+    /// The thread pool for the fitness calculation, started by `PopulationBuilder::finalize`
+    /// if `eval_threads` is greater than one and kept for the whole run. Clones of the
+    /// population share the pool.
+    pub eval_pool: Option<Arc<Mutex<Pool>>>,
+    /// The minimum diversity of the population. If the diversity (see `diversity`) drops below
+    /// this value, the worse half of the population is resetted before the next mutation step.
+    /// This needs the `distance` method of the `Individual` trait. Default: None (disabled).
+    pub min_diversity_floor: Option<f64>,
+    /// If this is set to k, each individual is mutated with `mutate_with_many` using the
+    /// k fittest individuals as parents instead of calling `mutate`. Default: None (disabled).
+    pub multi_parent_crossover: Option<usize>,
+    /// If this is set to n, in each iteration n children are created by calling `crossover`
+    /// on randomly chosen pairs of individuals. The children compete with the mutated and
+    /// the original individuals. Default: None (disabled).
+    pub crossover: Option<usize>,
+    /// The random number generator used by the population (crossover, steady state).
+    /// Use `PopulationBuilder::rng_seed` for reproducible runs.
+    pub rng: R,
+    /// If this is paused the population doesn't do any work.
+    /// Use `pause_handle` to pause and resume the population.
+    pub paused: PauseHandle,
+    /// When should the population be restarted ? Default: `RestartStrategy::None`, that means
+    /// the reset limit is used.
+    pub restart_strategy: RestartStrategy,
+    /// The number of iterations since the last restart.
+    pub restart_counter: u32,
+    /// The number of restarts so far.
+    pub num_of_restarts: u32,
+    /// If this is set to n, the population uses a steady state replacement instead of the
+    /// generational one: in each iteration n offspring are created from randomly chosen
+    /// individuals and each one replaces the least fit individual. Default: None (disabled).
+    pub steady_state: Option<usize>,
+    /// What to do when all individuals have the same fitness.
+    /// Default: `ConvergenceAction::Continue`.
+    pub on_full_convergence: ConvergenceAction,
+    /// True if all individuals had the same fitness after the last iteration.
+    pub converged: bool,
+    /// How many mutations the individuals do in each iteration.
+    /// Default: `MutationSchedule::Constant`.
+    pub mutation_schedule: MutationSchedule,
+    /// The number of iterations this population has done so far.
+    pub iteration_counter: u32,
+    /// The number of iterations since this population has found a new fittest individual.
+    pub stagnation_counter: u32,
+    /// Which individuals are resetted when the reset limit or a restart is reached.
+    /// Default: `ResetMethod::All`.
+    pub reset_method: ResetMethod,
+    /// Which individuals survive in each iteration. Default: `DeleteMethod::Truncate`.
+    pub delete_method: DeleteMethod,
+    /// How the individuals are ranked in each iteration. Default: `SortMethod::Fitness`.
+    pub sort_method: SortMethod,
+    /// Are lower or higher fitness values better ? The simulation sets this for all its
+    /// populations. Default: `OptimizationGoal::Minimize`.
+    pub goal: OptimizationGoal,
+}
+
+/// Calculates the fitness for the given individual without any penalty, using the weighted sum
+/// of its objectives if weights are given.
+fn raw_fitness<T: Individual>(weights: &[f64], individual: &mut T, rng: &mut XorShiftRng) -> f64 {
+    if weights.is_empty() {
+        individual.calculate_fitness_rng(rng)
+    } else {
+        let objectives = individual.objectives();
+        assert_eq!(objectives.len(), weights.len(), "Number of weights and objectives differ");
+        objectives.iter().zip(weights).map(|(objective, weight)| objective * weight).sum()
+    }
+}
+
+/// Calculates the fitness for the given individual, including the penalty for violated
+/// constraints. This is the fitness that is used for sorting.
+fn evaluate<T: Individual>(weights: &[f64], penalty_weight: f64, goal: OptimizationGoal, individual: &mut T,
+    rng: &mut XorShiftRng) -> f64 {
+    let mut fitness = raw_fitness(weights, individual, rng);
+
+    if penalty_weight > 0.0 {
+        let penalty = penalty_weight * individual.constraint_violation();
+
+        match goal {
+            OptimizationGoal::Minimize => fitness += penalty,
+            OptimizationGoal::Maximize => fitness -= penalty,
+        }
+    }
+
+    if fitness.is_nan() {
+        warn!("fitness of individual is NaN, it will be sorted to the end of the population");
+    }
+
+    fitness
+}
+
+/// Calculates the fitness of all the given individuals. If there is a thread pool, the
+/// individuals are split into `num_of_threads` chunks that are evaluated in parallel.
+/// Each individual gets its own random number generator, seeded from `base_seed` and its
+/// index, so the result doesn't depend on the number of threads.
+fn evaluate_all<T: Individual + Send>(wrappers: Vec<&mut IndividualWrapper<T>>, weights: &[f64],
+    penalty_weight: f64, goal: OptimizationGoal, base_seed: u32, pool: Option<&Mutex<Pool>>, num_of_threads: usize) {
+    let mut jobs: Vec<(u32, &mut IndividualWrapper<T>)> = wrappers.into_iter()
+        .enumerate()
+        .map(|(index, wrapper)| (base_seed ^ index as u32, wrapper))
+        .collect();
+
+    let pool = match pool {
+        Some(pool) if num_of_threads > 1 && jobs.len() > 1 => pool,
+        _ => {
+            for (seed, wrapper) in jobs {
+                wrapper.fitness = evaluate(weights, penalty_weight, goal, &mut wrapper.individual, &mut seeded_rng(seed));
+            }
+            return;
+        }
+    };
+
+    let chunk_size = jobs.len().div_ceil(num_of_threads);
+
+    pool.lock().unwrap().scope(|scope|
+        for chunk in jobs.chunks_mut(chunk_size) {
+            scope.submit(move ||
+                for &mut (seed, ref mut wrapper) in chunk.iter_mut() {
+                    wrapper.fitness = evaluate(weights, penalty_weight, goal, &mut wrapper.individual, &mut seeded_rng(seed));
+                });
+        });
 }
 
-impl<T: Individual + Send + Sync + Clone> Population<T> {
+impl<T: Individual + Send + Sync + Clone, R: Rng> Population<T, R> {
     /// Just calculates the fitness for each individual.
     /// Usually this is the most computational expensive operation, so optimize the
     /// `calculate_fitness` method of your data structure ;-)
     pub fn calculate_fitness(&mut self) {
+        self.evaluate_from(0);
+    }
+
+    /// Calculates the fitness of all individuals starting at index `start`, see `evaluate_all`.
+    fn evaluate_from(&mut self, start: usize) {
+        let base_seed = self.rng.gen::<u32>();
+        evaluate_all(self.population[start..].iter_mut().collect(), &self.weights, self.penalty_weight, self.goal,
+            base_seed, self.eval_pool.as_deref(), self.eval_threads);
+    }
+
+    /// Calculates the fitness for the given individual in the same way as for the individuals
+    /// of this population (i.e. using the weights if they are set).
+    pub fn evaluate(&self, individual: &mut T) -> f64 {
+        evaluate(&self.weights, self.penalty_weight, self.goal, individual, &mut rand::weak_rng())
+    }
+
+    /// Calculates the fitness for the given individual without the penalty for violated
+    /// constraints, i.e. the actual objective. This is useful for reporting.
+    pub fn raw_fitness(&self, individual: &mut T) -> f64 {
+        raw_fitness(&self.weights, individual, &mut rand::weak_rng())
+    }
+
+    /// Changes the weights of the objectives and recalculates the fitness for each individual.
+    /// This can be used to move along the pareto front during the simulation.
+    /// The number of weights must match the number of objectives.
+    pub fn set_weights(&mut self, weights: Vec<f64>) {
+        self.weights = weights;
+        self.calculate_fitness();
+        self.goal.sort(&mut self.population);
+    }
+
+    /// Changes the number of individuals of this population. If the population shrinks only the
+    /// fittest individuals are kept. If the population grows the existing individuals are
+    /// cloned to fill up the new slots.
+    pub fn resize(&mut self, num_of_individuals: u32) {
+        let new_len = num_of_individuals as usize;
+        let old_len = self.population.len();
+
+        if old_len == 0 {
+            return;
+        }
+
+        self.goal.sort(&mut self.population);
+        self.population.truncate(new_len);
+
+        for i in old_len..new_len {
+            let wrapper = self.population[i % old_len].clone();
+            self.population.push(wrapper);
+        }
+
+        self.num_of_individuals = num_of_individuals;
+    }
+
+    /// Sets the number of mutations for every individual of this population.
+    pub fn set_num_of_mutations(&mut self, num_of_mutations: u32) {
         for wrapper in &mut self.population {
-            wrapper.fitness = wrapper.individual.calculate_fitness();
+            wrapper.num_of_mutations = num_of_mutations;
         }
     }
 
-    /// This is the body that gets called for every iteration.
-    /// This function does the following:
-    ///
-    /// 1. Check if the reset limit is reached. If it is, this whole population is
-    /// discarded and re-initialized from the start. All the information about the
-    /// current fittest individual is lost. This is done to avoid local minima.
-    ///
-    /// 2. Clone the current population.
-    ///
-    /// 3. Mutate the current population using the `mutate_population` function.
-    ///
-    /// 4. Merge the newly mutated population and the original cloned population into one big
-    /// population twice the size.
-    ///
-    /// 5. Sort this new big population by fitness. So the fittest individual is at position 0.
-    ///
-    /// 6. Truncated the big population to its original size and thus gets rid of all the less fittest
-    /// individuals (they "die").
-    ///
-    /// 7. Check if the fittest individual (at index 0) in the current sorted population is better
-    /// (= fitter) than the global fittest individual of the whole simulation. If yes, the global
-    /// fittest individual is replaced.
-    ///
-    /// 8. Calculate the new improvement factor and prepare for the next iteration.
-    pub fn run_body(&mut self) {
-        // Is reset limit enabled ?
-        if self.reset_limit_end > 0 {
-            self.reset_counter += 1;
+    /// Inserts an individual that is shared by the simulation. If it is at least as fit as the
+    /// fittest individual of this population, it replaces the fittest individual. Otherwise
+    /// it replaces the least fit individual, but only if `accept_worse` is true and the
+    /// population has more than one individual (the fittest one is never replaced by a
+    /// worse one).
+    pub fn insert_shared(&mut self, wrapper: IndividualWrapper<T>, accept_worse: bool) {
+        if self.population.is_empty() {
+            return;
+        }
 
-            // Check if reset limit is reached
-            if self.reset_counter > self.reset_limit {
-                self.reset_limit += self.reset_limit_increment;
-                if self.reset_limit >= self.reset_limit_end {
-                    self.reset_limit = self.reset_limit_start;
-                    info!("reset_limit reset to reset_limit_start: {}, id: {}", self.reset_limit_start, self.id);
-                }
-                self.reset_counter = 0;
-                info!("new reset_limit: {}, id: {}, counter: {}", self.reset_limit, self.id, self.fitness_counter);
+        if !self.goal.is_better(self.population[0].fitness, wrapper.fitness) {
+            self.population[0] = wrapper;
+        } else if accept_worse && self.population.len() > 1 {
+            let last = self.population.len() - 1;
+            self.population[last] = wrapper;
+        }
+    }
 
-                // Kill all individuals since we are most likely stuck in a local minimum.
-                // Why is it so ? Because the simulation is still running and the exit criteria
-                // hasn't been reached yet!
-                // Keep number of mutations.
-                for wrapper in &mut self.population {
-                    wrapper.individual.reset();
-                    wrapper.fitness = wrapper.individual.calculate_fitness();
-                }
+    /// Returns a handle that allows to pause and resume this population from another thread.
+    /// Note that cloned populations share the same pause flag.
+    pub fn pause_handle(&self) -> PauseHandle {
+        self.paused.clone()
+    }
+
+    /// Returns the diversity of the population: the mean distance between all pairs of
+    /// individuals, using the `distance` method of the `Individual` trait. This needs O(n^2)
+    /// calls of `distance`. Without an implementation of `distance` the diversity is always 0.0.
+    pub fn diversity(&self) -> f64 {
+        let len = self.population.len();
+
+        if len < 2 {
+            return 0.0;
+        }
+
+        let mut sum = 0.0;
+
+        for (i, wrapper1) in self.population.iter().enumerate() {
+            for wrapper2 in &self.population[(i + 1)..] {
+                sum += wrapper1.individual.distance(&wrapper2.individual);
             }
         }
 
-        // Keep original population.
-        let orig_population = self.population.clone();
+        sum / ((len * (len - 1) / 2) as f64)
+    }
 
-        // Mutate population
+    /// Returns the number of individuals with an invalid fitness (NaN). These individuals
+    /// are treated as the least fit ones and usually get deleted in the next iteration.
+    pub fn count_invalid_fitness(&self) -> usize {
+        self.population.iter().filter(|wrapper| wrapper.fitness.is_nan()).count()
+    }
+
+    /// Returns the standard deviation of the fitness values of all individuals. This is a cheap
+    /// measure of diversity that also works if the `distance` method of the `Individual` trait
+    /// isn't implemented. A value of 0.0 means all individuals have the same fitness.
+    pub fn fitness_std_dev(&self) -> f64 {
+        let len = self.population.len();
+
+        if len < 2 {
+            return 0.0;
+        }
+
+        let mean = self.population.iter().map(|wrapper| wrapper.fitness).sum::<f64>() / (len as f64);
+        let variance = self.population.iter()
+            .map(|wrapper| (wrapper.fitness - mean) * (wrapper.fitness - mean))
+            .sum::<f64>() / (len as f64);
+
+        variance.sqrt()
+    }
+
+    /// Returns the number of distinct individuals in the population. Two individuals are
+    /// considered to be the same if their `distance` is zero. A population that consists
+    /// mostly of clones of the fittest individual has a small effective size, even if
+    /// the number of individuals is large. This needs O(n^2) calls of `distance`. Without an
+    /// implementation of `distance` the effective size is always 1.
+    pub fn effective_population_size(&self) -> usize {
+        let mut distinct: Vec<&T> = Vec::new();
+
+        for wrapper in &self.population {
+            if distinct.iter().all(|individual| individual.distance(&wrapper.individual) > 0.0) {
+                distinct.push(&wrapper.individual);
+            }
+        }
+
+        distinct.len()
+    }
+
+    /// Resets the worse half of the population in order to restore diversity.
+    /// The fittest individual is always kept, so a population with only one individual
+    /// is left untouched.
+    fn restore_diversity(&mut self) {
+        self.goal.sort(&mut self.population);
+
+        let half = (self.population.len() / 2).max(1);
+
+        for wrapper in &mut self.population[half..] {
+            wrapper.individual.reset();
+        }
+        self.evaluate_from(half);
+    }
+
+    /// Kill all individuals since we are most likely stuck in a local minimum.
+    /// Why is it so ? Because the simulation is still running and the exit criteria
+    /// hasn't been reached yet!
+    /// Depending on the reset method the fittest individuals are kept.
+    /// Keep number of mutations.
+    fn reset_all(&mut self) {
+        let keep = match self.reset_method {
+            ResetMethod::All => 0,
+            ResetMethod::WorstHalf => (self.population.len() / 2).max(1),
+            ResetMethod::KeepBest => 1,
+            ResetMethod::Perturb { strength } => {
+                self.perturb_all(strength);
+                return;
+            }
+        };
+
+        if keep > 0 {
+            self.goal.sort(&mut self.population);
+        }
+
+        let keep = keep.min(self.population.len());
+
+        for wrapper in &mut self.population[keep..] {
+            wrapper.individual.reset();
+        }
+        self.evaluate_from(keep);
+    }
+
+    /// Mutates every individual `strength` times in place and re-calculates its fitness.
+    /// No clones are created, so the size of the population stays the same.
+    fn perturb_all(&mut self, strength: u32) {
         for wrapper in &mut self.population {
-            for _ in 0..wrapper.num_of_mutations {
-                // Maybe add super optimization ?
-                // See https://github.com/willi-kappler/darwin-rs/issues/10
+            for _ in 0..strength {
                 wrapper.individual.mutate();
             }
-            wrapper.fitness = wrapper.individual.calculate_fitness();
+            wrapper.individual.repair();
         }
+        self.evaluate_from(0);
+    }
 
-        // Append original (unmutated) population to new (mutated) population.
-        self.population.extend(orig_population.iter().cloned());
+    /// Creates `replacements` offspring, each one replaces the least fit individual.
+    /// The fittest individual is never replaced by a worse one.
+    fn steady_state_step(&mut self, replacements: usize) {
+        self.goal.sort(&mut self.population);
 
-        // Sort by fitness
-        // Use random choice, see https://github.com/willi-kappler/darwin-rs/issues/7
-        self.population.sort();
+        let num_of_mutations: Vec<u32> = self.population.iter().map(|wrapper| wrapper.num_of_mutations).collect();
 
-        // Reduce population to original length.
-        self.population.truncate(self.num_of_individuals as usize);
+        for _ in 0..replacements {
+            let len = self.population.len();
+            let mut offspring = self.population[self.rng.gen_range(0, len)].clone();
+            let num_of_mutations = self.mutation_schedule.num_of_mutations(
+                offspring.num_of_mutations, self.iteration_counter, self.stagnation_counter);
+
+            for _ in 0..num_of_mutations {
+                offspring.individual.mutate();
+            }
+            offspring.individual.repair();
+            let mut rng = seeded_rng(self.rng.gen());
+            offspring.fitness = evaluate(&self.weights, self.penalty_weight, self.goal, &mut offspring.individual, &mut rng);
+
+            let worst = len - 1;
+            if worst > 0 || !self.goal.is_better(self.population[0].fitness, offspring.fitness) {
+                self.population[worst] = offspring;
+                self.goal.sort(&mut self.population);
+            }
+        }
 
         // Restore original number of mutation rate, since these will be lost because of sorting.
-        for (individual, orig_individual) in self.population
-            .iter_mut()
-            .zip(orig_population.iter()) {
-            individual.num_of_mutations = orig_individual.num_of_mutations;
+        for (wrapper, num_of_mutations) in self.population.iter_mut().zip(num_of_mutations) {
+            wrapper.num_of_mutations = num_of_mutations;
+        }
+    }
+
+    /// Creates a child of two different, randomly chosen parents using `crossover`.
+    /// The fitness of the child isn't calculated yet.
+    fn crossover_random_pair(&mut self, parents: &[IndividualWrapper<T>]) -> IndividualWrapper<T> {
+        let len = parents.len();
+
+        let index1 = self.rng.gen_range(0, len);
+        let mut index2 = index1;
+
+        if len > 1 {
+            index2 = self.rng.gen_range(0, len - 1);
+            if index2 >= index1 {
+                index2 += 1;
+            }
+        }
+
+        let mut individual = parents[index1].individual.crossover(&parents[index2].individual);
+        individual.repair();
+
+        IndividualWrapper {
+            individual,
+            fitness: f64::NAN,
+            num_of_mutations: parents[index1].num_of_mutations,
+            id: self.id,
+        }
+    }
+
+    /// Reduces the ranked population to its original length according to the delete method.
+    fn delete(&mut self) {
+        let num_of_individuals = self.num_of_individuals as usize;
+        let len = self.population.len();
+
+        if len <= num_of_individuals {
+            return;
+        }
+
+        match self.delete_method {
+            DeleteMethod::Truncate => {
+                // If keep_worst is set, some of the less fit individuals survive as well.
+                let keep_worst = self.keep_worst.min(num_of_individuals);
+                if keep_worst > 0 {
+                    let keep_best = num_of_individuals - keep_worst;
+                    let mut keep = vec![false; len];
+
+                    for i in (0..keep_best).chain(self.reservoir(keep_best, keep_worst)) {
+                        keep[i] = true;
+                    }
+
+                    self.retain(&keep);
+                } else {
+                    self.population.truncate(num_of_individuals);
+                }
+            }
+            DeleteMethod::Elitist { elite } => {
+                let elite = elite.min(num_of_individuals);
+                let mut keep = vec![false; len];
+                let mut others: Vec<usize> = (elite..len).collect();
+                self.rng.shuffle(&mut others);
+
+                for i in (0..elite).chain(others.into_iter().take(num_of_individuals - elite)) {
+                    keep[i] = true;
+                }
+
+                self.retain(&keep);
+            }
+            DeleteMethod::UniqueGenome => {
+                let mut kept: Vec<usize> = Vec::with_capacity(num_of_individuals);
+
+                for i in 0..len {
+                    if kept.len() == num_of_individuals {
+                        break;
+                    }
+                    let individual = &self.population[i].individual;
+                    if !kept.iter().any(|&j| self.population[j].individual.genome_eq(individual)) {
+                        kept.push(i);
+                    }
+                }
+
+                let mut keep = vec![false; len];
+                for &i in &kept {
+                    keep[i] = true;
+                }
+
+                // Not enough different individuals, fill up with the fittest duplicates.
+                let mut missing = num_of_individuals - kept.len();
+                for flag in &mut keep {
+                    if missing == 0 {
+                        break;
+                    }
+                    if !*flag {
+                        *flag = true;
+                        missing -= 1;
+                    }
+                }
+
+                self.retain(&keep);
+            }
+            DeleteMethod::Tournament { size } => {
+                let mut keep = vec![false; len];
+                let mut candidates: Vec<usize> = (0..len).collect();
+
+                for _ in 0..num_of_individuals {
+                    // The population is ranked, so the lowest index wins the tournament.
+                    let mut winner = self.rng.gen_range(0, candidates.len());
+                    for _ in 1..size {
+                        winner = winner.min(self.rng.gen_range(0, candidates.len()));
+                    }
+                    keep[candidates.remove(winner)] = true;
+                }
+
+                self.retain(&keep);
+            }
+        }
+    }
+
+    /// Keeps only the individuals marked in `keep`, the ranking of the survivors is kept.
+    /// Chooses `count` individuals after the `keep_best` fittest ones as a reservoir of
+    /// diversity (see `keep_worst`): the ones with the largest distance to the nearest of the
+    /// fittest individuals. If all distances are 0.0 (the default `distance`), the least fit
+    /// ones are chosen. Individuals with a fitness that is not finite are skipped, unless
+    /// there are not enough other individuals. The population must be ranked.
+    fn reservoir(&self, keep_best: usize, count: usize) -> Vec<usize> {
+        let (elite, others) = self.population.split_at(keep_best);
+
+        let mut candidates: Vec<(usize, f64)> = others.iter()
+            .enumerate()
+            .filter(|&(_, wrapper)| wrapper.fitness.is_finite())
+            .map(|(i, wrapper)| {
+                let distance = elite.iter()
+                    .map(|fittest| fittest.individual.distance(&wrapper.individual))
+                    .fold(f64::INFINITY, f64::min);
+                (keep_best + i, distance)
+            })
+            .collect();
+
+        if candidates.iter().all(|&(_, distance)| distance == 0.0) {
+            // No distance available, keep the least fit individuals.
+            candidates.reverse();
+        } else {
+            candidates.sort_by(|(_, distance1), (_, distance2)|
+                distance2.partial_cmp(distance1).unwrap_or(cmp::Ordering::Equal));
+        }
+
+        let mut chosen: Vec<usize> = candidates.into_iter().take(count).map(|(i, _)| i).collect();
+
+        // Not enough valid individuals, fill up with the fittest remaining ones.
+        for i in keep_best..self.population.len() {
+            if chosen.len() == count {
+                break;
+            }
+            if !chosen.contains(&i) {
+                chosen.push(i);
+            }
+        }
+
+        chosen
+    }
+
+    fn retain(&mut self, keep: &[bool]) {
+        let mut index = 0;
+        self.population.retain(|_| {
+            index += 1;
+            keep[index - 1]
+        });
+    }
+
+    /// Sorts the individuals according to the sort method.
+    fn sort(&mut self) {
+        match self.sort_method {
+            SortMethod::Fitness => self.goal.sort(&mut self.population),
+            SortMethod::Pareto => self.pareto_sort(),
+        }
+    }
+
+    /// Sorts the individuals by pareto fronts and then by fitness, see `SortMethod::Pareto`.
+    /// This calls the `objectives` method for every individual.
+    fn pareto_sort(&mut self) {
+        let goal = self.goal;
+        let objectives: Vec<Vec<f64>> = self.population.iter_mut()
+            .map(|wrapper| wrapper.individual.objectives())
+            .collect();
+
+        let mut front = vec![0; objectives.len()];
+        let mut remaining: Vec<usize> = (0..objectives.len()).collect();
+        let mut rank = 0;
+
+        while !remaining.is_empty() {
+            let (current, rest): (Vec<usize>, Vec<usize>) = remaining.iter().partition(|&&i|
+                !remaining.iter().any(|&j| dominates(goal, &objectives[j], &objectives[i])));
+
+            for i in current {
+                front[i] = rank;
+            }
+
+            remaining = rest;
+            rank += 1;
+        }
+
+        let mut ranked: Vec<(usize, IndividualWrapper<T>)> = self.population.drain(..).enumerate().collect();
+        ranked.sort_by(|&(i, ref wrapper1), &(j, ref wrapper2)|
+            front[i].cmp(&front[j]).then_with(|| goal.cmp(wrapper1, wrapper2)));
+        self.population = ranked.into_iter().map(|(_, wrapper)| wrapper).collect();
+    }
+
+    /// Returns the best fitness of all individuals, the population doesn't need to be sorted.
+    fn fittest_fitness(&self) -> Option<f64> {
+        self.fittest().map(|wrapper| wrapper.fitness)
+    }
+
+    /// Returns the fittest individual (with its fitness) of this population or None if the
+    /// population is empty. The population doesn't need to be sorted.
+    fn fittest(&self) -> Option<&IndividualWrapper<T>> {
+        let goal = self.goal;
+        self.population.iter()
+            .fold(None, |best: Option<&IndividualWrapper<T>>, wrapper| match best {
+                Some(best) if wrapper.fitness.is_nan() || !goal.is_better(wrapper.fitness, best.fitness) => Some(best),
+                _ => Some(wrapper),
+            })
+    }
+
+    /// Returns the fittest individual of this population or None if the population is empty.
+    pub fn best_individual(&self) -> Option<&T> {
+        self.fittest().map(|wrapper| &wrapper.individual)
+    }
+
+    /// Returns the fitness of the fittest individual of this population or None if the
+    /// population is empty.
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.fittest_fitness()
+    }
+
+    /// Returns true if the fittest individual of this population reports to be a solution
+    /// that can't be improved any further, see `Individual::is_solution`.
+    pub fn is_solved(&self) -> bool {
+        self.fittest().is_some_and(|wrapper| wrapper.individual.is_solution(wrapper.fitness))
+    }
+
+    /// Records an iteration in which nothing has been done, because the population is solved.
+    /// No reset or convergence action is triggered, so the solution is kept.
+    fn skip_iteration(&mut self) {
+        self.iteration_counter += 1;
+        self.stagnation_counter += 1;
+
+        if self.reset_limit_end > 0 {
+            self.reset_counter += 1;
+        }
+    }
+
+    /// Updates the iteration and stagnation counters at the end of an iteration and checks
+    /// for convergence.
+    fn finish_iteration(&mut self, old_fittest: Option<f64>) {
+        self.iteration_counter += 1;
+
+        let improved = match (old_fittest, self.population.first()) {
+            (Some(old_fittest), Some(wrapper)) => self.goal.is_better(wrapper.fitness, old_fittest),
+            _ => false,
+        };
+
+        if improved {
+            self.stagnation_counter = 0;
+        } else {
+            self.stagnation_counter += 1;
+        }
+
+        self.check_convergence();
+    }
+
+    /// Checks if the population has fully converged (the fittest and the least fit individual
+    /// have the same fitness) and takes the configured action. All individuals are compared,
+    /// since with `SortMethod::Pareto` the last one isn't necessarily the least fit.
+    /// A population with only one individual is never considered to be converged.
+    fn check_convergence(&mut self) {
+        let (lowest, highest) = self.population.iter()
+            .map(|wrapper| wrapper.fitness)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lowest, highest), fitness| (lowest.min(fitness), highest.max(fitness)));
+
+        self.converged = self.population.len() > 1 && lowest == highest;
+
+        if !self.converged {
+            return;
+        }
+
+        info!("CONVERGED: fitness: {}, id: {}, action: {:?}", self.population[0].fitness, self.id, self.on_full_convergence);
+
+        if self.on_full_convergence == ConvergenceAction::Restart {
+            self.reset_all();
+            self.sort();
+        }
+    }
+
+    /// Returns the number of iterations between the last and the next restart.
+    fn restart_interval(&self) -> u32 {
+        match self.restart_strategy {
+            RestartStrategy::None => 0,
+            RestartStrategy::FixedInterval { rounds } => rounds,
+            RestartStrategy::Doubling { base_rounds, .. } => {
+                // Clamp instead of losing the high bits.
+                base_rounds.checked_shl(self.num_of_restarts)
+                    .filter(|rounds| rounds >> self.num_of_restarts == base_rounds)
+                    .unwrap_or(u32::MAX)
+            }
+            RestartStrategy::Luby { unit } => unit.saturating_mul(luby(self.num_of_restarts.saturating_add(1))),
+        }
+    }
+
+    /// Checks if the population has to be restarted according to the restart strategy.
+    fn check_restart(&mut self) {
+        self.restart_counter = self.restart_counter.saturating_add(1);
+
+        if self.restart_counter <= self.restart_interval() {
+            return;
+        }
+
+        if let RestartStrategy::Doubling { max_individuals, .. } = self.restart_strategy {
+            let num_of_individuals = self.num_of_individuals.saturating_mul(2)
+                .min(max_individuals)
+                .max(self.num_of_individuals);
+            self.resize(num_of_individuals);
+        }
+
+        self.restart_counter = 0;
+        self.num_of_restarts += 1;
+        info!("restart: {}, num_of_individuals: {}, id: {}", self.num_of_restarts, self.num_of_individuals, self.id);
+
+        self.reset_all();
+    }
+
+    /// Runs exactly one iteration (generation) of this population by calling `run_body` and
+    /// returns true if a new fittest individual has been found.
+    /// This allows the user to write a custom simulation loop for a single population.
+    /// Make sure that `calculate_fitness` has been called before the first step.
+    pub fn step(&mut self) -> bool {
+        let old_fittest = self.fittest_fitness();
+
+        self.run_body();
+
+        match old_fittest {
+            Some(old_fittest) => self.goal.is_better(self.population[0].fitness, old_fittest),
+            None => false,
+        }
+    }
+
+    /// This is the body that gets called for every iteration.
+    /// This function does the following:
+    ///
+    /// 1. Check if the reset limit is reached. If it is, this whole population is
+    ///    discarded and re-initialized from the start. All the information about the
+    ///    current fittest individual is lost. This is done to avoid local minima.
+    ///
+    /// 2. Clone the current population.
+    ///
+    /// 3. Mutate the current population using the `mutate_population` function.
+    ///
+    /// 4. Merge the newly mutated population and the original cloned population into one big
+    ///    population twice the size (plus the children of the crossover, if enabled).
+    ///
+    /// 5. Sort this new big population by fitness. So the fittest individual is at position 0.
+    ///
+    /// 6. Truncated the big population to its original size and thus gets rid of all the less fittest
+    ///    individuals (they "die").
+    ///
+    /// 7. Check if the fittest individual (at index 0) in the current sorted population is better
+    ///    (= fitter) than the global fittest individual of the whole simulation. If yes, the global
+    ///    fittest individual is replaced.
+    ///
+    /// 8. Calculate the new improvement factor and prepare for the next iteration.
+    ///
+    /// If the population is paused, nothing is done and the state of the population is kept
+    /// as it is. This returns right away, the caller can use `PauseHandle::wait_while_paused`
+    /// to wait until the population is resumed.
+    pub fn run_body(&mut self) {
+        if self.paused.is_paused() {
+            return;
+        }
+
+        // Nothing left to improve, keep the solution instead of mutating or resetting.
+        if self.is_solved() {
+            self.skip_iteration();
+            return;
+        }
+
+        if self.restart_strategy != RestartStrategy::None {
+            self.check_restart();
+        } else if self.reset_limit_end > 0 {
+            // Reset limit is enabled
+            self.reset_counter += 1;
+
+            // Check if reset limit is reached
+            if self.reset_counter > self.reset_limit {
+                self.reset_limit += self.reset_limit_increment;
+                if self.reset_limit >= self.reset_limit_end {
+                    self.reset_limit = self.reset_limit_start;
+                    info!("reset_limit reset to reset_limit_start: {}, id: {}", self.reset_limit_start, self.id);
+                }
+                self.reset_counter = 0;
+                info!("new reset_limit: {}, id: {}, counter: {}", self.reset_limit, self.id, self.fitness_counter);
+
+                self.reset_all();
+            }
+        }
+
+        // Restore diversity if the population has collapsed.
+        if let Some(min_diversity_floor) = self.min_diversity_floor {
+            let diversity = self.diversity();
+            if diversity < min_diversity_floor {
+                info!("diversity too low: {}, id: {}", diversity, self.id);
+                self.restore_diversity();
+            }
+        }
+
+        let old_fittest = self.fittest_fitness();
+
+        if let Some(replacements) = self.steady_state {
+            self.steady_state_step(replacements);
+            self.finish_iteration(old_fittest);
+            return;
+        }
+
+        // Keep original population.
+        let orig_population = self.population.clone();
+
+        // Collect the fittest individuals as parents for the multi parent crossover.
+        let parents: Vec<&T> = match self.multi_parent_crossover {
+            Some(num_of_parents) => {
+                let mut sorted: Vec<&IndividualWrapper<T>> = orig_population.iter().collect();
+                sorted.sort_by(|wrapper1, wrapper2| self.goal.cmp(wrapper1, wrapper2));
+                sorted.iter().take(num_of_parents).map(|wrapper| &wrapper.individual).collect()
+            }
+            None => Vec::new(),
+        };
+
+        // Mutated individuals that failed the pre-screening, these are dropped.
+        let mut rejected = vec![false; self.population.len()];
+
+        // Mutate population
+        for (wrapper, rejected) in self.population.iter_mut().zip(rejected.iter_mut()) {
+            let num_of_mutations = self.mutation_schedule.num_of_mutations(
+                wrapper.num_of_mutations, self.iteration_counter, self.stagnation_counter);
+
+            for _ in 0..num_of_mutations {
+                // Maybe add super optimization ?
+                // See https://github.com/willi-kappler/darwin-rs/issues/10
+                if parents.is_empty() {
+                    wrapper.individual.mutate();
+                } else {
+                    wrapper.individual.mutate_with_many(&parents);
+                }
+            }
+            wrapper.individual.repair();
+
+            if let Some(prescreen_ratio) = self.prescreen_ratio {
+                if let Some(approximate_fitness) = wrapper.individual.approximate_fitness() {
+                    if self.goal.improvement(wrapper.fitness, approximate_fitness) < -prescreen_ratio * wrapper.fitness.abs() {
+                        // Clearly worse, the fitness is never calculated.
+                        *rejected = true;
+                    }
+                }
+            }
+        }
+
+        if rejected.contains(&true) {
+            let mut rejected = rejected.into_iter();
+            self.population.retain(|_| !rejected.next().unwrap_or(false));
+        }
+
+        // Add the children of randomly chosen pairs of the original population.
+        if let Some(num_of_children) = self.crossover {
+            let children: Vec<IndividualWrapper<T>> = (0..num_of_children)
+                .map(|_| self.crossover_random_pair(&orig_population))
+                .collect();
+            self.population.extend(children);
+        }
+
+        // Calculate the fitness of all mutated individuals that passed the pre-screening and
+        // of all children.
+        self.evaluate_from(0);
+
+        // Remember the original number of mutations, the original population is moved below.
+        let orig_num_of_mutations: Vec<u32> = orig_population.iter().map(|wrapper| wrapper.num_of_mutations).collect();
+
+        // Append original (unmutated) population to new (mutated) population.
+        // It is moved instead of cloned again, so there is only one clone per iteration.
+        self.population.extend(orig_population);
+
+        // Sort by fitness
+        // Use random choice, see https://github.com/willi-kappler/darwin-rs/issues/7
+        self.sort();
+
+        // Reduce population to original length.
+        self.delete();
+
+        // Restore original number of mutation rate, since these will be lost because of sorting.
+        for (individual, num_of_mutations) in self.population
+            .iter_mut()
+            .zip(orig_num_of_mutations) {
+            individual.num_of_mutations = num_of_mutations;
+        }
+
+        self.finish_iteration(old_fittest);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryFrom;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use rand::{Rng, SeedableRng, StdRng, XorShiftRng};
+
+    use super::{luby, dominates, RestartStrategy, ConvergenceAction, SortMethod, DeleteMethod,
+        MutationSchedule, ResetMethod};
+    use individual::{Individual, OptimizationGoal};
+    use population_builder::PopulationBuilder;
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest1 {
+        x: u32,
+    }
+
+    impl Individual for IndividualTest1 {
+        fn mutate(&mut self) {
+            // Each mutation makes the individual a bit worse, so the original individuals
+            // are always better than the mutated ones.
+            self.x += 100;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 0;
+        }
+    }
+
+    fn make_individuals(count: u32) -> Vec<IndividualTest1> {
+        (0..count).map(|x| IndividualTest1 { x }).collect()
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest2 {
+        x: f64,
+        counter: u32,
+    }
+
+    impl Individual for IndividualTest2 {
+        fn mutate(&mut self) {
+            self.counter += 1;
+            self.x += ((self.counter * 7) % 11) as f64 - 5.0;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            (self.x - 50.0) * (self.x - 50.0)
+        }
+
+        fn reset(&mut self) {
+            self.x = 0.0;
+        }
+    }
+
+    #[test]
+    fn step1() {
+        let individuals: Vec<IndividualTest2> = (0..10).map(|i| IndividualTest2 { x: 0.0, counter: i }).collect();
+
+        let mut population = PopulationBuilder::<IndividualTest2>::new()
+            .initial_population(&individuals)
+            .increasing_mutation_rate()
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+
+        let start_fitness = population.population[0].fitness;
+        let mut best_fitness = start_fitness;
+        let mut new_fittest_found = false;
+
+        for _ in 0..100 {
+            new_fittest_found |= population.step();
+            assert!(population.population[0].fitness <= best_fitness);
+            best_fitness = population.population[0].fitness;
+        }
+
+        assert!(new_fittest_found);
+        assert!(best_fitness < start_fitness);
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest3 {
+        a: f64,
+        b: f64,
+    }
+
+    impl Individual for IndividualTest3 {
+        fn mutate(&mut self) {
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.a
+        }
+
+        fn reset(&mut self) {
+        }
+
+        fn objectives(&mut self) -> Vec<f64> {
+            vec![self.a, self.b]
+        }
+    }
+
+    #[test]
+    fn weights1() {
+        let individuals = vec![IndividualTest3 { a: 1.0, b: 10.0 },
+                               IndividualTest3 { a: 10.0, b: 1.0 },
+                               IndividualTest3 { a: 20.0, b: 20.0 }];
+
+        let mut population = PopulationBuilder::<IndividualTest3>::new()
+            .initial_population(&individuals)
+            .weights(vec![1.0, 0.0])
+            .finalize().unwrap();
+
+        population.set_weights(vec![1.0, 0.0]);
+        assert_eq!(population.population[0].individual.a, 1.0);
+
+        population.set_weights(vec![0.0, 1.0]);
+        assert_eq!(population.population[0].individual.a, 10.0);
+    }
+
+    #[test]
+    fn weights2() {
+        let individuals = vec![IndividualTest3 { a: 1.0, b: 10.0 },
+                               IndividualTest3 { a: 10.0, b: 1.0 },
+                               IndividualTest3 { a: 20.0, b: 20.0 }];
+
+        let result = PopulationBuilder::<IndividualTest3>::new()
+            .initial_population(&individuals)
+            .weights(vec![1.0, 0.0, 0.5])
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest4 {
+        x: f64,
+        counter: u32,
+    }
+
+    impl Individual for IndividualTest4 {
+        fn mutate(&mut self) {
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            0.0
+        }
+
+        fn reset(&mut self) {
+            self.counter += 1;
+            self.x = (self.counter * 3) as f64;
+        }
+
+        fn distance(&self, other: &Self) -> f64 {
+            (self.x - other.x).abs()
+        }
+    }
+
+    #[test]
+    fn min_diversity_floor1() {
+        let individuals: Vec<IndividualTest4> = (0..10).map(|i| IndividualTest4 { x: 1.0, counter: i }).collect();
+
+        let mut population1 = PopulationBuilder::<IndividualTest4>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let mut population2 = PopulationBuilder::<IndividualTest4>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .min_diversity_floor(0.5)
+            .finalize().unwrap();
+
+        population1.calculate_fitness();
+        population2.calculate_fitness();
+
+        assert_eq!(population1.diversity(), 0.0);
+        assert_eq!(population2.diversity(), 0.0);
+
+        population1.run_body();
+        population2.run_body();
+
+        assert_eq!(population1.diversity(), 0.0);
+        assert!(population2.diversity() > 0.5);
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest5 {
+        x: u32,
+        parents: Vec<u32>,
+    }
+
+    impl Individual for IndividualTest5 {
+        fn mutate(&mut self) {
+            self.x += 100;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x as f64
+        }
+
+        fn reset(&mut self) {
+        }
+
+        fn mutate_with_many(&mut self, parents: &[&Self]) {
+            self.parents = parents.iter().map(|parent| parent.x).collect();
+            // The child is the best of all parents.
+            self.x = self.parents.iter().cloned().min().unwrap();
+        }
+    }
+
+    #[test]
+    fn multi_parent_crossover1() {
+        let individuals: Vec<IndividualTest5> = (0..10).rev().map(|x| IndividualTest5 { x, parents: Vec::new() }).collect();
+
+        let mut population = PopulationBuilder::<IndividualTest5>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .multi_parent_crossover(3)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.run_body();
+
+        // The children come first since they are equal to the best parent.
+        assert_eq!(population.population[0].individual.parents, vec![0, 1, 2]);
+        assert_eq!(population.population[0].fitness, 0.0);
+    }
+
+    #[test]
+    fn multi_parent_crossover2() {
+        let individuals: Vec<IndividualTest5> = (0..10).map(|x| IndividualTest5 { x, parents: Vec::new() }).collect();
+
+        let result = PopulationBuilder::<IndividualTest5>::new()
+            .initial_population(&individuals)
+            .multi_parent_crossover(11)
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn pause_handle1() {
+        let mut population = PopulationBuilder::<IndividualTest2>::new()
+            .initial_population(&vec![IndividualTest2 { x: 0.0, counter: 0 }; 5])
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let handle = population.pause_handle();
+
+        population.calculate_fitness();
+
+        handle.pause();
+        assert!(handle.is_paused());
+
+        for _ in 0..3 {
+            population.run_body();
+        }
+
+        assert!(population.population.iter().all(|wrapper| wrapper.individual.counter == 0));
+
+        handle.resume();
+        population.run_body();
+
+        assert!(population.population.iter().any(|wrapper| wrapper.individual.counter == 1));
+
+        // Waiting returns as soon as another thread resumes the population.
+        handle.pause();
+        let resumer = {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                handle.resume();
+            })
+        };
+
+        handle.wait_while_paused();
+        assert!(!handle.is_paused());
+        resumer.join().unwrap();
+    }
+
+    #[test]
+    fn effective_population_size1() {
+        let clones: Vec<IndividualTest4> = (0..10).map(|i| IndividualTest4 { x: if i < 8 { 1.0 } else { 2.0 }, counter: i }).collect();
+        let diverse: Vec<IndividualTest4> = (0..10).map(|i| IndividualTest4 { x: i as f64, counter: i }).collect();
+
+        let population1 = PopulationBuilder::<IndividualTest4>::new()
+            .initial_population(&clones)
+            .finalize().unwrap();
+
+        let population2 = PopulationBuilder::<IndividualTest4>::new()
+            .initial_population(&diverse)
+            .finalize().unwrap();
+
+        assert_eq!(population1.effective_population_size(), 2);
+        assert_eq!(population2.effective_population_size(), 10);
+    }
+
+    #[test]
+    fn fitness_std_dev1() {
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&[IndividualTest1 { x: 2 }, IndividualTest1 { x: 4 }, IndividualTest1 { x: 4 },
+                                  IndividualTest1 { x: 4 }, IndividualTest1 { x: 5 }, IndividualTest1 { x: 5 },
+                                  IndividualTest1 { x: 7 }, IndividualTest1 { x: 9 }])
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        assert_eq!(population.fitness_std_dev(), 2.0);
+
+        population.population.truncate(1);
+        assert_eq!(population.fitness_std_dev(), 0.0);
+    }
+
+    #[test]
+    fn luby1() {
+        let sequence: Vec<u32> = (1..16).map(luby).collect();
+
+        assert_eq!(sequence, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+        assert_eq!(luby(u32::MAX), 1 << 31);
+    }
+
+    #[test]
+    fn restart_strategy1() {
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(5))
+            .restart_strategy(RestartStrategy::Doubling { base_rounds: 2, max_individuals: 15 })
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+
+        let mut sizes = Vec::new();
+
+        for _ in 0..12 {
+            population.run_body();
+            sizes.push(population.population.len());
+        }
+
+        // Restarts after 2 and then after 4 more iterations, the size is limited to 15.
+        assert_eq!(sizes, vec![5, 5, 10, 10, 10, 10, 10, 15, 15, 15, 15, 15]);
+        assert_eq!(population.num_of_restarts, 2);
+
+        // After many restarts the interval is clamped instead of overflowing.
+        population.num_of_restarts = 40;
+        assert_eq!(population.restart_interval(), u32::MAX);
+
+        population.restart_strategy = RestartStrategy::Luby { unit: u32::MAX };
+        assert_eq!(population.restart_interval(), u32::MAX);
+    }
+
+    #[test]
+    fn keep_worst1() {
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .reset_limit_end(0)
+            .keep_worst(2)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.run_body();
+
+        let fitness: Vec<f64> = population.population.iter().map(|wrapper| wrapper.fitness).collect();
+
+        // The eight best original individuals and the two worst mutated ones.
+        assert_eq!(fitness, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 108.0, 109.0]);
+    }
+
+    #[test]
+    fn keep_worst2() {
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .keep_worst(10)
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn keep_worst3() {
+        // The fitness is x, the distance is given by y.
+        let individuals = vec![IndividualTest15 { x: 0.0, y: 0.0 }, IndividualTest15 { x: 1.0, y: 0.1 },
+            IndividualTest15 { x: 2.0, y: 5.0 }, IndividualTest15 { x: 3.0, y: 0.2 },
+            IndividualTest15 { x: f64::NAN, y: 100.0 }];
+
+        let mut population = PopulationBuilder::<IndividualTest15>::new()
+            .initial_population(&individuals)
+            .keep_worst(1)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.num_of_individuals = 2;
+        population.goal.sort(&mut population.population);
+        population.delete();
+
+        // The most distant valid individual is kept, not the worst one or the invalid one.
+        let x: Vec<f64> = population.population.iter().map(|wrapper| wrapper.individual.x).collect();
+        assert_eq!(x, vec![0.0, 2.0]);
+    }
+
+    #[test]
+    fn tiny_population1() {
+        for count in 1..3 {
+            let mut population = PopulationBuilder::<IndividualTest1>::new()
+                .initial_population(&make_individuals(count))
+                .reset_limit_end(0)
+                .keep_worst(count as usize - 1)
+                .multi_parent_crossover(count as usize)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+
+            for _ in 0..10 {
+                population.run_body();
+            }
+
+            assert_eq!(population.population.len(), count as usize);
+            assert_eq!(population.population[0].fitness, 0.0);
+        }
+    }
+
+    #[test]
+    fn tiny_population2() {
+        let individuals = vec![IndividualTest4 { x: 1.0, counter: 0 }];
+
+        let mut population = PopulationBuilder::<IndividualTest4>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .min_diversity_floor(1.0)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.run_body();
+
+        // A single individual is never resetted to restore diversity.
+        assert_eq!(population.population[0].individual.x, 1.0);
+
+        let mut shared = population.population[0].clone();
+        shared.fitness = 10.0;
+        population.insert_shared(shared, true);
+
+        // The only (and fittest) individual is not replaced by a worse one.
+        assert_eq!(population.population[0].fitness, 0.0);
+    }
+
+    #[test]
+    fn tiny_population3() {
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(0))
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest6 {
+        x: i64,
+    }
+
+    impl Individual for IndividualTest6 {
+        fn mutate(&mut self) {
+            // Each mutation makes the individual better than all the others.
+            self.x -= 100;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 0;
+        }
+    }
+
+    #[test]
+    fn steady_state1() {
+        let individuals: Vec<IndividualTest6> = (0..10).map(|x| IndividualTest6 { x }).collect();
+
+        let mut population = PopulationBuilder::<IndividualTest6>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .steady_state(3)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.run_body();
+
+        let mut original: Vec<i64> = population.population.iter()
+            .map(|wrapper| wrapper.individual.x)
+            .filter(|x| *x >= 0)
+            .collect();
+        original.sort();
+
+        // The three worst individuals have been replaced by offspring.
+        assert_eq!(population.population.len(), 10);
+        assert_eq!(original, vec![0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn steady_state2() {
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .reset_limit_end(0)
+            .steady_state(4)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+
+        let mut best_fitness = population.population[0].fitness;
+
+        for _ in 0..10 {
+            population.run_body();
+            assert!(population.population[0].fitness <= best_fitness);
+            best_fitness = population.population[0].fitness;
+        }
+
+        assert_eq!(population.population.len(), 10);
+    }
+
+    #[test]
+    fn steady_state3() {
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .steady_state(0)
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn on_full_convergence1() {
+        let individuals: Vec<IndividualTest4> = (0..10).map(|i| IndividualTest4 { x: 1.0, counter: i }).collect();
+
+        let mut population = PopulationBuilder::<IndividualTest4>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.run_body();
+
+        // Continue: the population is not touched.
+        assert!(population.converged);
+        assert!(population.population.iter().all(|wrapper| wrapper.individual.x == 1.0));
+
+        population.on_full_convergence = ConvergenceAction::Restart;
+        population.run_body();
+
+        // Restart: all the individuals have been resetted.
+        assert!(population.converged);
+        assert!(population.population.iter().all(|wrapper| wrapper.individual.x != 1.0));
+    }
+
+    #[test]
+    fn on_full_convergence2() {
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .reset_limit_end(0)
+            .on_full_convergence(ConvergenceAction::Restart)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.run_body();
+
+        assert!(!population.converged);
+        assert_eq!(population.population[9].fitness, 9.0);
+    }
+
+    #[test]
+    fn on_full_convergence3() {
+        // (1, 5) dominates (1, 6), so the pareto order is (1, 5), (2, 1), (1, 6): the first and
+        // the last individual have the same fitness, but the population hasn't converged.
+        let individuals = vec![IndividualTest3 { a: 1.0, b: 6.0 },
+                               IndividualTest3 { a: 2.0, b: 1.0 },
+                               IndividualTest3 { a: 1.0, b: 5.0 }];
+
+        let mut population = PopulationBuilder::<IndividualTest3>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .sort_method(SortMethod::Pareto)
+            .on_full_convergence(ConvergenceAction::Restart)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.pareto_sort();
+        let b: Vec<f64> = population.population.iter().map(|wrapper| wrapper.individual.b).collect();
+        assert_eq!(b, vec![5.0, 1.0, 6.0]);
+
+        population.check_convergence();
+        assert!(!population.converged);
+
+        // A converged population is sorted by pareto fronts again after the restart.
+        population.population.retain(|wrapper| wrapper.fitness == 1.0);
+        population.population.reverse();
+        population.check_convergence();
+
+        assert!(population.converged);
+        let b: Vec<f64> = population.population.iter().map(|wrapper| wrapper.individual.b).collect();
+        assert_eq!(b, vec![5.0, 6.0]);
+    }
+
+    #[test]
+    fn goal1() {
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .reset_limit_end(0)
+            .goal(OptimizationGoal::Maximize)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+
+        // Each mutation increases the fitness, so with maximization every step finds a new best.
+        for i in 1..4 {
+            assert!(population.step());
+            assert_eq!(population.population[0].fitness, (9 + (i * 100)) as f64);
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest7 {
+        x: u32,
+    }
+
+    impl Individual for IndividualTest7 {
+        fn mutate(&mut self) {
+            self.x += 100;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 0;
+        }
+
+        fn crossover(&self, other: &Self) -> Self {
+            IndividualTest7 { x: (self.x + other.x) / 2 }
+        }
+    }
+
+    #[test]
+    fn crossover1() {
+        let individuals = vec![IndividualTest7 { x: 10 }, IndividualTest7 { x: 30 }];
+
+        let mut population = PopulationBuilder::<IndividualTest7>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .crossover(1)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.run_body();
+
+        let x: Vec<u32> = population.population.iter().map(|wrapper| wrapper.individual.x).collect();
+
+        // The child of both parents is better than the mutated individuals and the worse parent.
+        assert_eq!(x, vec![10, 20]);
+    }
+
+    #[test]
+    fn crossover2() {
+        let result = PopulationBuilder::<IndividualTest7>::new()
+            .initial_population(&[IndividualTest7 { x: 10 }])
+            .crossover(0)
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rng_seed1() {
+        let individuals: Vec<IndividualTest7> = (0..10).map(|x| IndividualTest7 { x: x * 7 }).collect();
+
+        let run = |steady_state| {
+            let builder = PopulationBuilder::<IndividualTest7>::new()
+                .initial_population(&individuals)
+                .reset_limit_end(0)
+                .crossover(3)
+                .rng_seed(42);
+
+            let mut population = if steady_state {
+                builder.steady_state(2).finalize().unwrap()
+            } else {
+                builder.finalize().unwrap()
+            };
+
+            population.calculate_fitness();
+
+            let mut fitness = Vec::new();
+
+            for _ in 0..20 {
+                population.run_body();
+                fitness.extend(population.population.iter().map(|wrapper| wrapper.fitness));
+            }
+
+            fitness
+        };
+
+        assert_eq!(run(false), run(false));
+        assert_eq!(run(true), run(true));
+    }
+
+    struct ZeroRng;
+
+    impl Rng for ZeroRng {
+        fn next_u32(&mut self) -> u32 {
+            0
+        }
+    }
+
+    #[test]
+    fn rng_seed2() {
+        let individuals: Vec<IndividualTest7> = (0..10).map(|x| IndividualTest7 { x: x * 7 }).collect();
+
+        let run = || {
+            let mut population = PopulationBuilder::<IndividualTest7, StdRng>::with_rng(StdRng::from_seed(&[1, 2, 3, 4][..]))
+                .initial_population(&individuals)
+                .reset_limit_end(0)
+                .crossover(3)
+                .delete_method(DeleteMethod::Elitist { elite: 2 })
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+
+            for _ in 0..20 {
+                population.run_body();
+            }
+
+            let indices: Vec<usize> = (0..10).map(|_| population.rng.gen_range(0, 100)).collect();
+            let fitness: Vec<f64> = population.population.iter().map(|wrapper| wrapper.fitness).collect();
+            (indices, fitness)
+        };
+
+        assert_eq!(run(), run());
+
+        // With a mock generator the tournament always picks the first candidate, which is the
+        // fittest remaining individual.
+        let mut population = PopulationBuilder::<IndividualTest1, ZeroRng>::with_rng(ZeroRng)
+            .initial_population(&make_individuals(5))
+            .reset_limit_end(0)
+            .delete_method(DeleteMethod::Tournament { size: 2 })
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.run_body();
+
+        let fitness: Vec<f64> = population.population.iter().map(|wrapper| wrapper.fitness).collect();
+        assert_eq!(fitness, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn pareto1() {
+        let individuals = vec![IndividualTest3 { a: 2.0, b: 11.0 },
+                               IndividualTest3 { a: 10.0, b: 1.0 },
+                               IndividualTest3 { a: 3.0, b: 20.0 },
+                               IndividualTest3 { a: 4.0, b: 4.0 },
+                               IndividualTest3 { a: 5.0, b: 5.0 },
+                               IndividualTest3 { a: 1.0, b: 10.0 }];
+
+        let mut population = PopulationBuilder::<IndividualTest3>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .sort_method(SortMethod::Pareto)
+            .finalize().unwrap();
+
+        // Keep only three of the six individuals after the next iteration.
+        population.calculate_fitness();
+        population.num_of_individuals = 3;
+        population.run_body();
+
+        // Only the non-dominated individuals survive, even though (2, 11) has a better fitness
+        // than (4, 4).
+        for wrapper in &population.population {
+            let kept = vec![wrapper.individual.a, wrapper.individual.b];
+            for individual in &individuals {
+                assert!(!dominates(OptimizationGoal::Minimize, &[individual.a, individual.b], &kept));
+            }
+        }
+
+        let a: Vec<f64> = population.population.iter().map(|wrapper| wrapper.individual.a).collect();
+        assert_eq!(a, vec![1.0, 1.0, 4.0]);
+    }
+
+    #[test]
+    fn elitist1() {
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .reset_limit_end(0)
+            .delete_method(DeleteMethod::Elitist { elite: 1 })
+            .rng_seed(7)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+
+        for _ in 0..50 {
+            population.run_body();
+
+            // The population is flooded with worse mutated clones, but the best one survives.
+            assert_eq!(population.population.len(), 10);
+            assert_eq!(population.population[0].fitness, 0.0);
+        }
+
+        assert!(population.population.iter().any(|wrapper| wrapper.fitness >= 100.0));
+    }
+
+    #[test]
+    fn elitist2() {
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .delete_method(DeleteMethod::Elitist { elite: 11 })
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn tournament1() {
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .reset_limit_end(0)
+            .delete_method(DeleteMethod::Tournament { size: 3 })
+            .rng_seed(11)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.run_body();
+
+        let fitness: Vec<f64> = population.population.iter().map(|wrapper| wrapper.fitness).collect();
+        let mut sorted = fitness.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        // The survivors are still ranked, but some worse individuals survive.
+        assert_eq!(fitness.len(), 10);
+        assert_eq!(fitness, sorted);
+        assert!(fitness.iter().any(|fitness| *fitness >= 100.0));
+    }
+
+    #[test]
+    fn tournament2() {
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .delete_method(DeleteMethod::Tournament { size: 0 })
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn delete_method1() {
+        assert_eq!("truncate".parse::<DeleteMethod>(), Ok(DeleteMethod::Truncate));
+        assert_eq!("elitist:3".parse::<DeleteMethod>(), Ok(DeleteMethod::Elitist { elite: 3 }));
+        assert_eq!("tournament:4".parse::<DeleteMethod>(), Ok(DeleteMethod::Tournament { size: 4 }));
+        assert!("tournament".parse::<DeleteMethod>().is_err());
+        assert!("elitist:x".parse::<DeleteMethod>().is_err());
+        assert!("random".parse::<DeleteMethod>().is_err());
+
+        assert_eq!(DeleteMethod::Tournament { size: 4 }.to_string(), "tournament:4");
+
+        assert_eq!(DeleteMethod::try_from(2), Ok(DeleteMethod::Tournament { size: 2 }));
+        assert_eq!("unique_genome".parse::<DeleteMethod>(), Ok(DeleteMethod::UniqueGenome));
+        assert_eq!(DeleteMethod::try_from(3), Ok(DeleteMethod::UniqueGenome));
+        assert!(DeleteMethod::try_from(4).is_err());
+    }
+
+    #[test]
+    fn delete_method2() {
+        let methods = [DeleteMethod::Truncate, DeleteMethod::Elitist { elite: 3 }, DeleteMethod::Tournament { size: 4 },
+            DeleteMethod::UniqueGenome];
+
+        for method in &methods {
+            assert_eq!(method.to_string().parse::<DeleteMethod>().as_ref(), Ok(method));
+        }
+
+        for code in 0..4 {
+            let method = DeleteMethod::try_from(code).unwrap();
+            assert_eq!(method.to_string().parse::<DeleteMethod>(), Ok(method));
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest8 {
+        mutations: u32,
+    }
+
+    impl Individual for IndividualTest8 {
+        fn mutate(&mut self) {
+            self.mutations += 1;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            0.0
+        }
+
+        fn reset(&mut self) {
+            self.mutations = 0;
+        }
+    }
+
+    #[test]
+    fn mutation_schedule1() {
+        let mut population = PopulationBuilder::<IndividualTest8>::new()
+            .initial_population(&[IndividualTest8 { mutations: 0 }, IndividualTest8 { mutations: 0 }])
+            .reset_limit_end(0)
+            .mutation_schedule(MutationSchedule::LinearDecay { start: 10, end: 2, iterations: 4 })
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+
+        let mut mutations = Vec::new();
+        let mut total = 0;
+
+        for _ in 0..6 {
+            population.run_body();
+            // All fitness values are equal, so the mutated individuals survive.
+            mutations.push(population.population[0].individual.mutations - total);
+            total = population.population[0].individual.mutations;
+        }
+
+        assert_eq!(mutations, vec![10, 8, 6, 4, 2, 2]);
+    }
+
+    #[test]
+    fn mutation_schedule2() {
+        let schedule = MutationSchedule::AdaptiveStagnation { max: 10 };
+
+        assert_eq!(schedule.num_of_mutations(2, 100, 0), 2);
+        assert_eq!(schedule.num_of_mutations(2, 100, 3), 5);
+        assert_eq!(schedule.num_of_mutations(2, 100, 50), 10);
+        assert_eq!(MutationSchedule::Constant.num_of_mutations(7, 100, 50), 7);
+    }
+
+    #[test]
+    fn mutation_schedule3() {
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&make_individuals(10))
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+        population.run_body();
+        population.run_body();
+
+        // Mutations only make IndividualTest1 worse, so there is no improvement.
+        assert_eq!(population.iteration_counter, 2);
+        assert_eq!(population.stagnation_counter, 2);
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest9 {
+        fitness: f64,
+    }
+
+    impl Individual for IndividualTest9 {
+        fn mutate(&mut self) {
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.fitness
+        }
+
+        fn reset(&mut self) {
+        }
+    }
+
+    #[test]
+    fn invalid_fitness1() {
+        let individuals: Vec<IndividualTest9> = [2.0, f64::NAN, 1.0, 3.0].iter()
+            .map(|&fitness| IndividualTest9 { fitness })
+            .collect();
+
+        for &goal in &[OptimizationGoal::Minimize, OptimizationGoal::Maximize] {
+            let mut population = PopulationBuilder::<IndividualTest9>::new()
+                .initial_population(&individuals)
+                .goal(goal)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+            assert_eq!(population.count_invalid_fitness(), 1);
+
+            population.num_of_individuals = 3;
+            goal.sort(&mut population.population);
+            population.delete();
+
+            assert_eq!(population.population.len(), 3);
+            assert_eq!(population.count_invalid_fitness(), 0);
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest10 {
+        x: u32,
+    }
+
+    impl Individual for IndividualTest10 {
+        fn mutate(&mut self) {
+            self.x += 7;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            assert!(self.x <= 10, "individual has not been repaired");
+            f64::from(10 - self.x)
+        }
+
+        fn reset(&mut self) {
+            self.x = 0;
+        }
+
+        fn repair(&mut self) {
+            self.x = self.x.min(10);
+        }
+    }
+
+    #[test]
+    fn repair1() {
+        let individuals: Vec<IndividualTest10> = (0..5).map(|x| IndividualTest10 { x }).collect();
+
+        for &steady_state in &[false, true] {
+            let builder = PopulationBuilder::<IndividualTest10>::new()
+                .initial_population(&individuals)
+                .reset_limit_end(0)
+                .crossover(2);
+
+            let mut population = if steady_state {
+                builder.steady_state(2).finalize().unwrap()
+            } else {
+                builder.finalize().unwrap()
+            };
+
+            population.calculate_fitness();
+
+            for _ in 0..5 {
+                population.run_body();
+                assert!(population.population.iter().all(|wrapper| wrapper.individual.x <= 10));
+            }
+
+            assert_eq!(population.population[0].individual.x, 10);
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest11 {
+        x: f64,
+        violation: f64,
+    }
+
+    impl Individual for IndividualTest11 {
+        fn mutate(&mut self) {
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x
+        }
+
+        fn reset(&mut self) {
+        }
+
+        fn constraint_violation(&self) -> f64 {
+            self.violation
+        }
+    }
+
+    #[test]
+    fn penalty_weight1() {
+        let individuals = vec![
+            IndividualTest11 { x: 1.0, violation: 2.0 },
+            IndividualTest11 { x: 3.0, violation: 0.0 },
+        ];
+
+        for &(goal, x) in &[(OptimizationGoal::Minimize, 1.0), (OptimizationGoal::Maximize, 5.0)] {
+            let mut individuals = individuals.clone();
+            individuals[0].x = x;
+
+            let mut population = PopulationBuilder::<IndividualTest11>::new()
+                .initial_population(&individuals)
+                .penalty_weight(10.0)
+                .goal(goal)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+            goal.sort(&mut population.population);
+
+            // The individual that violates the constraint is worse, even
+            // though its actual objective is better.
+            assert_eq!(population.population[0].individual.violation, 0.0);
+            assert_eq!(population.population[0].fitness, 3.0);
+            assert_eq!(population.raw_fitness(&mut individuals[0]), x);
+        }
+
+        let result = PopulationBuilder::<IndividualTest11>::new()
+            .initial_population(&individuals)
+            .penalty_weight(-1.0)
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn reset_method1() {
+        let individuals: Vec<IndividualTest6> = (0..10).map(|x| IndividualTest6 { x: x - 1000 }).collect();
+
+        let run = |reset_method| {
+            let mut population = PopulationBuilder::<IndividualTest6>::new()
+                .initial_population(&individuals)
+                .reset_limit_start(2)
+                .reset_limit_end(100)
+                .reset_limit_increment(1)
+                .reset_method(reset_method)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+
+            let mut best = Vec::new();
+
+            for _ in 0..20 {
+                population.run_body();
+                best.push(population.population[0].fitness);
+            }
+
+            best
+        };
+
+        let never_regresses = |best: &[f64]| best.windows(2).all(|pair| pair[1] <= pair[0]);
+
+        assert!(never_regresses(&run(ResetMethod::KeepBest)));
+        assert!(never_regresses(&run(ResetMethod::WorstHalf)));
+        assert!(!never_regresses(&run(ResetMethod::All)));
+    }
+
+    // Queens problem: one queen per column, the value is the row of the queen.
+    #[derive(Debug, Clone)]
+    struct IndividualTest12 {
+        rows: Vec<usize>,
+    }
+
+    impl Individual for IndividualTest12 {
+        fn mutate(&mut self) {
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            let mut num_of_collisions = 0;
+
+            for col1 in 0..self.rows.len() {
+                for col2 in (col1 + 1)..self.rows.len() {
+                    let row1 = self.rows[col1] as i64;
+                    let row2 = self.rows[col2] as i64;
+                    if (row1 == row2) || ((row1 - row2).abs() == (col2 - col1) as i64) {
+                        num_of_collisions += 1;
+                    }
+                }
+            }
+
+            num_of_collisions as f64
+        }
+
+        fn reset(&mut self) {
+        }
+
+        fn genome_eq(&self, other: &Self) -> bool {
+            self.rows == other.rows
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest13 {
+        x: i64,
+    }
+
+    impl Individual for IndividualTest13 {
+        fn mutate(&mut self) {
+            self.x += 1;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 1000;
+        }
+    }
+
+    #[test]
+    fn reset_method2() {
+        let individuals: Vec<IndividualTest13> = (0..10).map(|x| IndividualTest13 { x }).collect();
+
+        let reset = |reset_method| {
+            let mut population = PopulationBuilder::<IndividualTest13>::new()
+                .initial_population(&individuals)
+                .reset_method(reset_method)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+            population.reset_all();
+
+            let mut fitness: Vec<f64> = population.population.iter().map(|wrapper| wrapper.fitness).collect();
+            fitness.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            fitness
+        };
+
+        // The perturbed individuals stay close to the original ones.
+        assert_eq!(reset(ResetMethod::Perturb { strength: 3 }), (0..10).map(|x| (x + 3) as f64).collect::<Vec<f64>>());
+        assert_eq!(reset(ResetMethod::All), vec![1000.0; 10]);
+
+        let result = PopulationBuilder::<IndividualTest13>::new()
+            .initial_population(&individuals)
+            .reset_method(ResetMethod::Perturb { strength: 0 })
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    static EVALUATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest14 {
+        x: i64,
+    }
+
+    impl Individual for IndividualTest14 {
+        fn mutate(&mut self) {
+            // Even individuals get better, odd ones get worse.
+            if self.x % 2 == 1 {
+                self.x += 2;
+            } else {
+                self.x -= 2;
+            }
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            EVALUATIONS.fetch_add(1, Ordering::SeqCst);
+            self.x as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 0;
+        }
+
+        fn approximate_fitness(&self) -> Option<f64> {
+            Some(self.x as f64)
+        }
+    }
+
+    #[test]
+    fn prescreen_ratio1() {
+        let individuals: Vec<IndividualTest14> = (10..20).map(|x| IndividualTest14 { x }).collect();
+
+        let evaluations = |prescreen_ratio: Option<f64>, keep_worst: usize| {
+            let mut builder = PopulationBuilder::<IndividualTest14>::new()
+                .initial_population(&individuals)
+                .keep_worst(keep_worst)
+                .reset_limit_end(0);
+
+            if let Some(prescreen_ratio) = prescreen_ratio {
+                builder = builder.prescreen_ratio(prescreen_ratio);
+            }
+
+            let mut population = builder.finalize().unwrap();
+
+            population.calculate_fitness();
+            let before = EVALUATIONS.load(Ordering::SeqCst);
+            population.run_body();
+
+            // Rejected individuals never enter the population, even if the worst ones are kept.
+            assert_eq!(population.population.len(), 10);
+            assert_eq!(population.population[0].fitness, 8.0);
+            assert!(population.population.iter().all(|wrapper| wrapper.fitness.is_finite()));
+            assert!(population.fitness_std_dev().is_finite());
+
+            EVALUATIONS.load(Ordering::SeqCst) - before
+        };
+
+        // Only the improved (even) individuals need a full evaluation.
+        assert_eq!(evaluations(None, 0), 10);
+        assert_eq!(evaluations(Some(0.0), 0), 5);
+        assert_eq!(evaluations(Some(0.0), 2), 5);
+        // The odd individuals are 2 / 11 = 18% worse at most.
+        assert_eq!(evaluations(Some(0.2), 0), 10);
+
+        let result = PopulationBuilder::<IndividualTest14>::new()
+            .initial_population(&individuals)
+            .prescreen_ratio(-0.5)
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn run_body1() {
+        let individuals: Vec<IndividualTest6> = (0..10).map(|x| IndividualTest6 { x }).collect();
+
+        let mut population = PopulationBuilder::<IndividualTest6>::new()
+            .initial_population(&individuals)
+            .mutation_rate(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10])
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+
+        for _ in 0..10 {
+            population.run_body();
+        }
+
+        // The same result as before the original population was moved instead of cloned, and
+        // the number of mutations stays at its index.
+        assert_eq!(population.population.len(), 10);
+        assert_eq!(population.population[0].fitness, -5500.0);
+
+        let num_of_mutations: Vec<u32> = population.population.iter().map(|wrapper| wrapper.num_of_mutations).collect();
+        assert_eq!(num_of_mutations, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn eval_threads1() {
+        let individuals: Vec<IndividualTest6> = (0..10).map(|x| IndividualTest6 { x }).collect();
+
+        let run = |eval_threads: usize| {
+            let mut population = PopulationBuilder::<IndividualTest6>::new()
+                .initial_population(&individuals)
+                .mutation_rate(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10])
+                .reset_limit_end(0)
+                .eval_threads(eval_threads)
+                .rng_seed(1)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+
+            for _ in 0..10 {
+                population.run_body();
+            }
+
+            population.population.iter().map(|wrapper| wrapper.fitness).collect::<Vec<f64>>()
+        };
+
+        // The parallel evaluation gives the same fitness values as the sequential one.
+        let sequential = run(1);
+        assert_eq!(sequential[0], -5500.0);
+        assert_eq!(run(3), sequential);
+        assert_eq!(run(20), sequential);
+
+        // The crossover children are evaluated by the threads as well.
+        let individuals: Vec<IndividualTest7> = (0..10).map(|x| IndividualTest7 { x: x * 7 }).collect();
+
+        let run = |eval_threads: usize| {
+            let mut population = PopulationBuilder::<IndividualTest7>::new()
+                .initial_population(&individuals)
+                .reset_limit_end(0)
+                .crossover(5)
+                .eval_threads(eval_threads)
+                .rng_seed(1)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+            population.run_body();
+
+            population.population.iter().map(|wrapper| wrapper.fitness).collect::<Vec<f64>>()
+        };
+
+        let sequential = run(1);
+        assert!(sequential.iter().all(|fitness| fitness.is_finite()));
+        assert_eq!(run(4), sequential);
+    }
+
+    #[test]
+    fn eval_threads2() {
+        let individuals: Vec<IndividualTest16> = (0..20).map(|x| IndividualTest16 { x: x as f64 }).collect();
+
+        let run = |eval_threads: usize| {
+            let mut population = PopulationBuilder::<IndividualTest16>::new()
+                .initial_population(&individuals)
+                .reset_limit_end(0)
+                .eval_threads(eval_threads)
+                .rng_seed(5)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+
+            for _ in 0..5 {
+                population.run_body();
+            }
+
+            population.population.iter().map(|wrapper| wrapper.fitness).collect::<Vec<f64>>()
+        };
+
+        // The noisy fitness only depends on the seed, not on the number of threads.
+        let sequential = run(1);
+        assert!(sequential.iter().any(|fitness| fitness.fract() != 0.0));
+        assert_eq!(run(1), sequential);
+        assert_eq!(run(4), sequential);
+        assert_eq!(run(4), sequential);
+    }
+
+    #[test]
+    fn unique_genome1() {
+        // Two different boards with the same number of collisions.
+        let board1 = IndividualTest12 { rows: vec![0, 2, 0, 2] };
+        let board2 = IndividualTest12 { rows: vec![1, 3, 1, 3] };
+        let individuals = vec![board1.clone(), board1, board2, IndividualTest12 { rows: vec![0; 4] }];
+
+        let expected = vec![
+            (DeleteMethod::UniqueGenome, vec![vec![0, 2, 0, 2], vec![1, 3, 1, 3]]),
+            (DeleteMethod::Truncate, vec![vec![0, 2, 0, 2], vec![0, 2, 0, 2]]),
+        ];
+
+        for (delete_method, survivors) in expected {
+            let mut population = PopulationBuilder::<IndividualTest12>::new()
+                .initial_population(&individuals)
+                .delete_method(delete_method)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+            assert_eq!(population.population[0].fitness, population.population[2].fitness);
+
+            population.num_of_individuals = 2;
+            population.goal.sort(&mut population.population);
+            population.delete();
+
+            let rows: Vec<Vec<usize>> = population.population.iter().map(|wrapper| wrapper.individual.rows.clone()).collect();
+            assert_eq!(rows, survivors);
+        }
+    }
+
+    #[test]
+    fn unique_genome2() {
+        // Four different boards with the same number of collisions, each one twice.
+        let boards = vec![vec![0, 2, 0, 2], vec![1, 3, 1, 3], vec![2, 0, 2, 0], vec![3, 1, 3, 1]];
+        let mut rows: Vec<Vec<usize>> = boards.iter().chain(boards.iter().rev()).cloned().collect();
+
+        // The result must not depend on where the duplicates end up after sorting.
+        for _ in 0..rows.len() {
+            rows.rotate_left(1);
+            let individuals: Vec<IndividualTest12> = rows.iter().map(|rows| IndividualTest12 { rows: rows.clone() }).collect();
+
+            let mut population = PopulationBuilder::<IndividualTest12>::new()
+                .initial_population(&individuals)
+                .delete_method(DeleteMethod::UniqueGenome)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+            assert!(population.population.iter().all(|wrapper| wrapper.fitness == 2.0));
+
+            population.num_of_individuals = 4;
+            population.goal.sort(&mut population.population);
+            population.delete();
+
+            let mut survivors: Vec<Vec<usize>> = population.population.iter().map(|wrapper| wrapper.individual.rows.clone()).collect();
+            survivors.sort();
+            assert_eq!(survivors, boards);
+        }
+    }
+
+    #[test]
+    fn best_individual1() {
+        let individuals: Vec<IndividualTest1> = [5, 2, 7].iter().map(|&x| IndividualTest1 { x }).collect();
+
+        for &(goal, best) in &[(OptimizationGoal::Minimize, 2), (OptimizationGoal::Maximize, 7)] {
+            let mut population = PopulationBuilder::<IndividualTest1>::new()
+                .initial_population(&individuals)
+                .goal(goal)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+
+            assert_eq!(population.best_individual().unwrap().x, best);
+            assert_eq!(population.best_fitness(), Some(best as f64));
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest15 {
+        x: f64,
+        y: f64,
+    }
+
+    impl Individual for IndividualTest15 {
+        fn mutate(&mut self) {
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x
+        }
+
+        fn reset(&mut self) {
+        }
+
+        fn distance(&self, other: &Self) -> f64 {
+            (self.y - other.y).abs()
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest16 {
+        x: f64,
+    }
+
+    impl Individual for IndividualTest16 {
+        fn mutate(&mut self) {
+            self.x -= 1.0;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x
+        }
+
+        fn calculate_fitness_rng(&mut self, rng: &mut XorShiftRng) -> f64 {
+            // A noisy measurement.
+            self.x + rng.gen::<f64>()
+        }
+
+        fn reset(&mut self) {
         }
     }
 }
@@ -13,32 +13,77 @@
 //!
 //!
 
-use std;
+use std::sync::{Arc, Mutex};
 
-use individual::{Individual, IndividualWrapper};
-use population::Population;
+use rand::{self, Rng, XorShiftRng};
+use jobsteal::make_pool;
+
+use individual::{Individual, IndividualWrapper, OptimizationGoal};
+use population::{Population, RestartStrategy, ConvergenceAction, SortMethod, DeleteMethod, MutationSchedule,
+    ResetMethod, PauseHandle};
+use util::seeded_rng;
 
 /// This is a helper struct in order to build (configure) a valid population.
 /// See builder pattern: https://en.wikipedia.org/wiki/Builder_pattern
 ///
 /// Maybe use phantom types, see https://github.com/willi-kappler/darwin-rs/issues/9
-pub struct PopulationBuilder<T: Individual> {
+pub struct PopulationBuilder<T: Individual, R: Rng = XorShiftRng> {
     /// The actual simulation
-    population: Population<T>,
+    population: Population<T, R>,
+    /// The mutation rates set with `mutation_rate`, checked and applied in `finalize`.
+    mutation_rate: Option<Vec<u32>>,
 }
 
 error_chain! {
     errors {
         IndividualsTooLow
         LimitEndTooLow
+        KeepWorstTooHigh
+        WeightsMismatch
+        MultiParentOutOfRange
+        RestartIntervalTooLow
+        SteadyStateOutOfRange
+        CrossoverChildrenTooLow
+        NumOfMutationsTooLow
+        EliteOutOfRange
+        TournamentSizeTooLow
+        PenaltyWeightOutOfRange
+        PrescreenRatioOutOfRange
+        MutationRateMismatch
+        EvalThreadsTooLow
+        EvalThreadsNotStarted
+    }
+}
+
+impl<T: Individual + Clone> Default for PopulationBuilder<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-/// This implementation contains all the helper method to build (configure) a valid population.
 impl<T: Individual + Clone> PopulationBuilder<T> {
     /// Start with this method, it must always be called as the first one.
     /// It creates a default population with some dummy (but invalid) values.
     pub fn new() -> PopulationBuilder<T> {
+        PopulationBuilder::with_rng(rand::weak_rng())
+    }
+
+    /// Seeds the random number generator of the population, so that two populations with the
+    /// same seed (and the same individuals) make the same random choices. Note that the
+    /// `Individual` methods (for example `mutate`) have to use their own seeded random number
+    /// generator for a fully reproducible run. By default the generator is seeded randomly.
+    pub fn rng_seed(mut self, seed: u32) -> PopulationBuilder<T> {
+        self.population.rng = seeded_rng(seed);
+        self
+    }
+}
+
+/// This implementation contains all the helper method to build (configure) a valid population.
+impl<T: Individual + Clone, R: Rng> PopulationBuilder<T, R> {
+    /// Use this instead of `new` in order to use a different random number generator for the
+    /// population, for example a seeded `StdRng` or a mock generator in tests.
+    /// Note that `Simulation` only works with the default generator.
+    pub fn with_rng(rng: R) -> PopulationBuilder<T, R> {
         PopulationBuilder {
             population: Population {
                 num_of_individuals: 0,
@@ -49,19 +94,93 @@ impl<T: Individual + Clone> PopulationBuilder<T> {
                 reset_limit_increment: 1000,
                 reset_counter: 0,
                 id: 1,
-                fitness_counter: 0
-            }
+                fitness_counter: 0,
+                last_improvement: None,
+                keep_worst: 0,
+                weights: Vec::new(),
+                penalty_weight: 0.0,
+                prescreen_ratio: None,
+                eval_threads: 1,
+                eval_pool: None,
+                min_diversity_floor: None,
+                multi_parent_crossover: None,
+                crossover: None,
+                rng,
+                paused: PauseHandle::default(),
+                restart_strategy: RestartStrategy::None,
+                restart_counter: 0,
+                num_of_restarts: 0,
+                steady_state: None,
+                on_full_convergence: ConvergenceAction::Continue,
+                converged: false,
+                mutation_schedule: MutationSchedule::Constant,
+                iteration_counter: 0,
+                stagnation_counter: 0,
+                reset_method: ResetMethod::All,
+                delete_method: DeleteMethod::Truncate,
+                sort_method: SortMethod::Fitness,
+                goal: OptimizationGoal::Minimize,
+            },
+            mutation_rate: None,
         }
     }
 
-    /// Sets the initial population provided inside a vector, length must be >= 3
-    pub fn initial_population(mut self, individuals: &[T]) -> PopulationBuilder<T> {
+    /// Sets the initial population provided inside a vector, length must be >= 1
+    pub fn initial_population(mut self, individuals: &[T]) -> PopulationBuilder<T, R> {
         self.population.num_of_individuals = individuals.len() as u32;
 
         for individual in individuals {
             self.population.population.push(IndividualWrapper {
                 individual: (*individual).clone(),
-                fitness: std::f64::MAX,
+                fitness: f64::MAX,
+                num_of_mutations: 1,
+                id: self.population.id,
+            });
+        }
+
+        self
+    }
+
+    /// Sets the initial population to the given (for example hand crafted or heuristic)
+    /// individuals. If fewer than `num_of_individuals` individuals are given, the population is
+    /// filled up with mutated clones of them (in the given order). If more individuals are
+    /// given, the least fit ones are deleted in the first iteration.
+    /// At least one individual must be given.
+    pub fn initial_population_padded(mut self, individuals: &[T], num_of_individuals: u32) -> PopulationBuilder<T, R> {
+        if individuals.is_empty() {
+            return self;
+        }
+
+        self = self.initial_population(individuals);
+        self.population.num_of_individuals = num_of_individuals;
+
+        for individual in individuals.iter().cycle().take((num_of_individuals as usize).saturating_sub(individuals.len())) {
+            let mut individual = individual.clone();
+            individual.mutate();
+            individual.repair();
+
+            self.population.population.push(IndividualWrapper {
+                individual,
+                fitness: f64::MAX,
+                num_of_mutations: 1,
+                id: self.population.id,
+            });
+        }
+
+        self
+    }
+
+    /// Sets the initial population by creating `num_of_individuals` individuals around the
+    /// given `initial` individual. Each new individual is created by calling the
+    /// `perturb_from` method of the `Individual` trait with the given `spread`.
+    /// This is useful if a good estimate of the solution is already known.
+    pub fn initial_population_spread(mut self, initial: &T, num_of_individuals: u32, spread: f64) -> PopulationBuilder<T, R> {
+        self.population.num_of_individuals = num_of_individuals;
+
+        for _ in 0..num_of_individuals {
+            self.population.population.push(IndividualWrapper {
+                individual: initial.perturb_from(spread),
+                fitness: f64::MAX,
                 num_of_mutations: 1,
                 id: self.population.id,
             });
@@ -73,12 +192,9 @@ impl<T: Individual + Clone> PopulationBuilder<T> {
     /// Configures the mutation rates (number of mutation runs) for all the individuals
     /// in the population: The first individual will mutate once, the second will mutate twice,
     /// the nth individual will Mutate n-times per iteration.
-    pub fn increasing_mutation_rate(mut self) -> PopulationBuilder<T> {
-        let mut mutation_rate = 1;
-
-        for wrapper in &mut self.population.population {
+    pub fn increasing_mutation_rate(mut self) -> PopulationBuilder<T, R> {
+        for (mutation_rate, wrapper) in (1..).zip(self.population.population.iter_mut()) {
             wrapper.num_of_mutations = mutation_rate;
-            mutation_rate += 1;
         }
 
         self
@@ -89,12 +205,9 @@ impl<T: Individual + Clone> PopulationBuilder<T> {
     /// `increasing_mutation_rate` function above this sets an exponention mutation rate for
     /// all the individuals. The first individual will mutate base^1 times, the second will
     /// mutate base^2 times, and nth will mutate base^n times per iteration.
-    pub fn increasing_exp_mutation_rate(mut self, base: f64) -> PopulationBuilder<T> {
-        let mut mutation_rate = 1;
-
-        for wrapper in &mut self.population.population {
+    pub fn increasing_exp_mutation_rate(mut self, base: f64) -> PopulationBuilder<T, R> {
+        for (mutation_rate, wrapper) in (1..).zip(self.population.population.iter_mut()) {
             wrapper.num_of_mutations = base.powi(mutation_rate).floor() as u32;
-            mutation_rate += 1;
         }
 
         self
@@ -102,18 +215,10 @@ impl<T: Individual + Clone> PopulationBuilder<T> {
 
     /// Configures the mutation rates (number of mutation runs) for all the individuals in the
     /// population: This allows to specify an arbitrary mutation scheme for each individual.
-    /// The number of rates must be equal to the number of individuals.
-    pub fn mutation_rate(mut self, mutation_rate: Vec<u32>) -> PopulationBuilder<T> {
-        // TODO: better error handling
-        assert!(self.population.population.len() == mutation_rate.len());
-
-        for (individual, mutation_rate) in self.population
-            .population
-            .iter_mut()
-            .zip(mutation_rate.into_iter()) {
-            individual.num_of_mutations = mutation_rate;
-        }
-
+    /// The number of rates must be equal to the number of individuals, this is checked in
+    /// `finalize`. The rates are applied in `finalize` and override the other mutation rates.
+    pub fn mutation_rate(mut self, mutation_rate: Vec<u32>) -> PopulationBuilder<T, R> {
+        self.mutation_rate = Some(mutation_rate);
         self
     }
 
@@ -121,7 +226,7 @@ impl<T: Individual + Clone> PopulationBuilder<T> {
     /// then a reset counter is increased each iteration. If that counter is greater than the
     /// limit, all individuals will be resetted, the limit will be increased by 1000 and the
     /// counter is set back to zero. Default value for reset_limit_start is 1000.
-    pub fn reset_limit_start(mut self, reset_limit_start: u32) -> PopulationBuilder<T> {
+    pub fn reset_limit_start(mut self, reset_limit_start: u32) -> PopulationBuilder<T, R> {
         self.population.reset_limit_start = reset_limit_start;
         self.population.reset_limit = reset_limit_start;
         self
@@ -131,20 +236,158 @@ impl<T: Individual + Clone> PopulationBuilder<T> {
     /// then the reset_limit will be resetted to the start value reset_limit_start.
     /// Default value for reset_limit_end is 100000.
     /// If reset_limit_end == 0 then the reset limit feature will be disabled.
-    pub fn reset_limit_end(mut self, reset_limit_end: u32) -> PopulationBuilder<T> {
+    pub fn reset_limit_end(mut self, reset_limit_end: u32) -> PopulationBuilder<T, R> {
         self.population.reset_limit_end = reset_limit_end;
         self
     }
 
     /// Configure the increment for the reset_limit. If the reset_limit is reached, its value
     /// is incrementet by the amount of reset_limit_increment.
-    pub fn reset_limit_increment(mut self, reset_limit_increment: u32) -> PopulationBuilder<T> {
+    pub fn reset_limit_increment(mut self, reset_limit_increment: u32) -> PopulationBuilder<T, R> {
         self.population.reset_limit_increment = reset_limit_increment;
         self
     }
 
+    /// Configures the number of less fit individuals that survive each iteration.
+    /// This keeps a small reservoir of diverse individuals which preserves genetic diversity:
+    /// the ones furthest away from the fittest individuals (see the `distance` method of the
+    /// `Individual` trait), or the worst ones if `distance` isn't implemented.
+    /// Must be lower than the number of individuals. Default value is 0.
+    pub fn keep_worst(mut self, keep_worst: usize) -> PopulationBuilder<T, R> {
+        self.population.keep_worst = keep_worst;
+        self
+    }
+
+    /// Configures the weights for the objectives of the individuals. If weights are given the
+    /// fitness of each individual is the weighted sum of its `objectives`.
+    /// The number of weights must match the number of objectives.
+    pub fn weights(mut self, weights: Vec<f64>) -> PopulationBuilder<T, R> {
+        self.population.weights = weights;
+        self
+    }
+
+    /// Configures the weight of the constraint violation of the individuals (see the
+    /// `constraint_violation` method of the `Individual` trait). The weighted violation is added
+    /// to the fitness as a penalty, so infeasible individuals are sorted further down.
+    /// Must not be negative. Default value is 0.0 (no penalty).
+    pub fn penalty_weight(mut self, penalty_weight: f64) -> PopulationBuilder<T, R> {
+        self.population.penalty_weight = penalty_weight;
+        self
+    }
+
+    /// Configures the minimum diversity of the population. If the mean distance between the
+    /// individuals drops below this value the worse half of the population is resetted.
+    /// The `distance` method of the `Individual` trait must be implemented for this to work.
+    pub fn min_diversity_floor(mut self, min_diversity_floor: f64) -> PopulationBuilder<T, R> {
+        self.population.min_diversity_floor = Some(min_diversity_floor);
+        self
+    }
+
+    /// Configures the number of parents for the multi parent crossover. If this is set, each
+    /// individual is mutated with `mutate_with_many` using the fittest `num_of_parents`
+    /// individuals as parents. Must be between 1 and the number of individuals.
+    pub fn multi_parent_crossover(mut self, num_of_parents: usize) -> PopulationBuilder<T, R> {
+        self.population.multi_parent_crossover = Some(num_of_parents);
+        self
+    }
+
+    /// Configures the number of children that are created in each iteration by calling the
+    /// `crossover` method of the `Individual` trait on randomly chosen pairs of individuals.
+    /// Must be at least 1.
+    pub fn crossover(mut self, num_of_children: usize) -> PopulationBuilder<T, R> {
+        self.population.crossover = Some(num_of_children);
+        self
+    }
+
+    /// Configures when the population is restarted. If this is set, the reset limit is
+    /// not used. Default value is `RestartStrategy::None`.
+    pub fn restart_strategy(mut self, restart_strategy: RestartStrategy) -> PopulationBuilder<T, R> {
+        self.population.restart_strategy = restart_strategy;
+        self
+    }
+
+    /// Configures a steady state replacement: in each iteration `replacements` offspring are
+    /// created and each one replaces the least fit individual, instead of mutating the whole
+    /// population. Must be between 1 and the number of individuals.
+    /// Note that `keep_worst` and `multi_parent_crossover` are not used in this mode.
+    pub fn steady_state(mut self, replacements: usize) -> PopulationBuilder<T, R> {
+        self.population.steady_state = Some(replacements);
+        self
+    }
+
+    /// Configures what happens when all individuals of the population have the same fitness.
+    /// Default value is `ConvergenceAction::Continue`.
+    pub fn on_full_convergence(mut self, action: ConvergenceAction) -> PopulationBuilder<T, R> {
+        self.population.on_full_convergence = action;
+        self
+    }
+
+    /// Configures how many mutations the individuals do in each iteration. The number of
+    /// mutations given by the schedule must always be at least 1.
+    /// Default value is `MutationSchedule::Constant`.
+    pub fn mutation_schedule(mut self, mutation_schedule: MutationSchedule) -> PopulationBuilder<T, R> {
+        self.population.mutation_schedule = mutation_schedule;
+        self
+    }
+
+    /// Enables the pre-screening of mutated individuals with the cheap `approximate_fitness`
+    /// method of the `Individual` trait: if the approximation is worse than the fitness before
+    /// the mutation by more than `prescreen_ratio` times the absolute fitness, the individual
+    /// is discarded without calling `calculate_fitness`. With 0.0 only approximations that are
+    /// at least as good pass. Must not be negative. Default value is None (disabled).
+    pub fn prescreen_ratio(mut self, prescreen_ratio: f64) -> PopulationBuilder<T, R> {
+        self.population.prescreen_ratio = Some(prescreen_ratio);
+        self
+    }
+
+    /// Calculates the fitness of the mutated individuals and of the crossover children with this
+    /// number of threads (including the thread that runs the population). The threads are
+    /// started once in `finalize`. This only pays off if `calculate_fitness` is expensive.
+    /// The `calculate_fitness` method of the `Individual` trait must not depend on the order of
+    /// the evaluations, a stochastic fitness should implement `calculate_fitness_rng` instead.
+    /// Must be at least 1. Default value is 1.
+    pub fn eval_threads(mut self, eval_threads: usize) -> PopulationBuilder<T, R> {
+        self.population.eval_threads = eval_threads;
+        self
+    }
+
+    /// Configures which individuals are resetted when the reset limit or a restart is reached.
+    /// With `ResetMethod::KeepBest`, `ResetMethod::WorstHalf` or `ResetMethod::Perturb` the
+    /// progress of the population isn't lost completely. The strength of `ResetMethod::Perturb`
+    /// must be at least 1. Default value is `ResetMethod::All`.
+    pub fn reset_method(mut self, reset_method: ResetMethod) -> PopulationBuilder<T, R> {
+        self.population.reset_method = reset_method;
+        self
+    }
+
+    /// Configures which individuals survive in each iteration. With `DeleteMethod::Elitist`
+    /// the number of elite individuals must be between 1 and the number of individuals,
+    /// with `DeleteMethod::Tournament` the size must be at least 1.
+    /// Note that `keep_worst` is only used with `DeleteMethod::Truncate`.
+    /// Default value is `DeleteMethod::Truncate`.
+    pub fn delete_method(mut self, delete_method: DeleteMethod) -> PopulationBuilder<T, R> {
+        self.population.delete_method = delete_method;
+        self
+    }
+
+    /// Configures how the individuals are ranked before the least fit ones are removed.
+    /// Use `SortMethod::Pareto` for multi objective problems, the `objectives` method of the
+    /// `Individual` trait must be implemented for this. Default value is `SortMethod::Fitness`.
+    pub fn sort_method(mut self, sort_method: SortMethod) -> PopulationBuilder<T, R> {
+        self.population.sort_method = sort_method;
+        self
+    }
+
+    /// Configures if lower or higher fitness values are better. This is only needed if the
+    /// population is used on its own (see `Population::step`), since the simulation sets the
+    /// goal for all its populations. Default value is `OptimizationGoal::Minimize`.
+    pub fn goal(mut self, goal: OptimizationGoal) -> PopulationBuilder<T, R> {
+        self.population.goal = goal;
+        self
+    }
+
     /// Set the population id. Currently this is only used for statistics.
-    pub fn set_id(mut self, id: u32) -> PopulationBuilder<T> {
+    pub fn set_id(mut self, id: u32) -> PopulationBuilder<T, R> {
         for individual in &mut self.population.population {
             individual.id = id;
         }
@@ -155,16 +398,277 @@ impl<T: Individual + Clone> PopulationBuilder<T> {
 
     /// This checks the configuration of the simulation and returns an PopError or Ok if no PopErrors
     /// where found.
-    pub fn finalize(self) -> Result<Population<T>> {
+    pub fn finalize(mut self) -> Result<Population<T, R>> {
+        if let Some(mutation_rate) = self.mutation_rate.take() {
+            if mutation_rate.len() != self.population.population.len() {
+                return Err(ErrorKind::MutationRateMismatch.into());
+            }
+
+            for (individual, mutation_rate) in self.population
+                .population
+                .iter_mut()
+                .zip(mutation_rate) {
+                individual.num_of_mutations = mutation_rate;
+            }
+        }
+
+        if !self.population.weights.is_empty() {
+            if let Some(wrapper) = self.population.population.first() {
+                let num_of_objectives = wrapper.individual.clone().objectives().len();
+                if num_of_objectives != self.population.weights.len() {
+                    return Err(ErrorKind::WeightsMismatch.into());
+                }
+            }
+        }
+
+        if self.population.population.iter().any(|wrapper| wrapper.num_of_mutations == 0) {
+            return Err(ErrorKind::NumOfMutationsTooLow.into());
+        }
+
+        match self.population.mutation_schedule {
+            MutationSchedule::LinearDecay { start: 0, .. } |
+            MutationSchedule::LinearDecay { end: 0, .. } |
+            MutationSchedule::AdaptiveStagnation { max: 0 } => {
+                return Err(ErrorKind::NumOfMutationsTooLow.into());
+            }
+            _ => {}
+        }
+
+        if self.population.reset_method == (ResetMethod::Perturb { strength: 0 }) {
+            return Err(ErrorKind::NumOfMutationsTooLow.into());
+        }
+
         match self.population {
-            Population { num_of_individuals: 0..=2, ..} => {
+            Population { num_of_individuals: 0, ..} => {
                 Err(ErrorKind::IndividualsTooLow.into())
             }
             Population { reset_limit_start: start,
                          reset_limit_end: end, ..} if (end > 0) && (start >= end) => {
                 Err(ErrorKind::LimitEndTooLow.into())
             }
-            _ => Ok(self.population)
+            Population { num_of_individuals: num, keep_worst: worst, ..} if worst >= num as usize => {
+                Err(ErrorKind::KeepWorstTooHigh.into())
+            }
+            Population { restart_strategy: RestartStrategy::FixedInterval { rounds: 0 }, ..} |
+            Population { restart_strategy: RestartStrategy::Doubling { base_rounds: 0, .. }, ..} |
+            Population { restart_strategy: RestartStrategy::Luby { unit: 0 }, ..} => {
+                Err(ErrorKind::RestartIntervalTooLow.into())
+            }
+            Population { num_of_individuals: num, multi_parent_crossover: Some(parents), ..}
+                if (parents == 0) || (parents > num as usize) => {
+                Err(ErrorKind::MultiParentOutOfRange.into())
+            }
+            Population { num_of_individuals: num, steady_state: Some(replacements), ..}
+                if (replacements == 0) || (replacements > num as usize) => {
+                Err(ErrorKind::SteadyStateOutOfRange.into())
+            }
+            Population { num_of_individuals: num, delete_method: DeleteMethod::Elitist { elite }, ..}
+                if (elite == 0) || (elite > num as usize) => {
+                Err(ErrorKind::EliteOutOfRange.into())
+            }
+            Population { delete_method: DeleteMethod::Tournament { size: 0 }, ..} => {
+                Err(ErrorKind::TournamentSizeTooLow.into())
+            }
+            Population { crossover: Some(0), ..} => {
+                Err(ErrorKind::CrossoverChildrenTooLow.into())
+            }
+            Population { penalty_weight, ..} if !(penalty_weight >= 0.0 && penalty_weight.is_finite()) => {
+                Err(ErrorKind::PenaltyWeightOutOfRange.into())
+            }
+            Population { prescreen_ratio: Some(ratio), ..} if !(ratio >= 0.0 && ratio.is_finite()) => {
+                Err(ErrorKind::PrescreenRatioOutOfRange.into())
+            }
+            Population { eval_threads: 0, ..} => {
+                Err(ErrorKind::EvalThreadsTooLow.into())
+            }
+            _ => {
+                let mut population = self.population;
+
+                if population.eval_threads > 1 {
+                    // The calling thread evaluates individuals too, so one thread less is started.
+                    let pool = make_pool(population.eval_threads - 1)
+                        .chain_err(|| ErrorKind::EvalThreadsNotStarted)?;
+                    population.eval_pool = Some(Arc::new(Mutex::new(pool)));
+                }
+
+                Ok(population)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{PopulationBuilder, ErrorKind};
+    use individual::Individual;
+
+    static NOISE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    // Deterministic "noise" in the range [-1.0, 1.0]
+    fn next_noise() -> f64 {
+        let n = NOISE_COUNTER.fetch_add(1, Ordering::SeqCst);
+        ((n * 37) % 201) as f64 / 100.0 - 1.0
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest1 {
+        x: f64,
+    }
+
+    impl Individual for IndividualTest1 {
+        fn mutate(&mut self) {
+            self.x += next_noise();
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            (self.x - 3.0) * (self.x - 3.0)
+        }
+
+        fn reset(&mut self) {
+            self.x = 0.0;
+        }
+
+        fn perturb_from(&self, scale: f64) -> Self {
+            IndividualTest1 { x: self.x + (scale * next_noise()) }
+        }
+    }
+
+    #[test]
+    fn initial_population_spread1() {
+        let mut seed = IndividualTest1 { x: 5.0 };
+        let seed_fitness = seed.calculate_fitness();
+
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population_spread(&seed, 20, 0.01)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+
+        assert_eq!(population.population.len(), 20);
+
+        for wrapper in &population.population {
+            assert!((wrapper.fitness - seed_fitness).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn initial_population_padded1() {
+        let individuals = vec![IndividualTest1 { x: 3.0 }, IndividualTest1 { x: 10.0 }];
+
+        let mut population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population_padded(&individuals, 5)
+            .finalize().unwrap();
+
+        population.calculate_fitness();
+
+        assert_eq!(population.num_of_individuals, 5);
+        assert_eq!(population.population.len(), 5);
+        assert_eq!(population.population[0].fitness, 0.0);
+        assert_eq!(population.population[1].fitness, 49.0);
+        assert!(population.population.iter().all(|wrapper| wrapper.fitness != f64::MAX));
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population_padded(&individuals, 1)
+            .finalize().unwrap();
+
+        assert_eq!(population.population.len(), 2);
+
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population_padded(&[], 5)
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn finalize1() {
+        let individuals = vec![IndividualTest1 { x: 1.0 }, IndividualTest1 { x: 2.0 }];
+
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .mutation_rate(vec![1, 0])
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::NumOfMutationsTooLow)),
+            Ok(_) => panic!("zero mutations accepted"),
+        }
+
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .mutation_rate(vec![1, 2, 3])
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::MutationRateMismatch)),
+            Ok(_) => panic!("wrong number of mutation rates accepted"),
+        }
+
+        // base^n is rounded down, so a base below 1.0 gives zero mutations.
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .increasing_exp_mutation_rate(0.5)
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::NumOfMutationsTooLow)),
+            Ok(_) => panic!("zero mutations accepted"),
+        }
+    }
+
+    #[test]
+    fn finalize2() {
+        let individuals = vec![IndividualTest1 { x: 1.0 }, IndividualTest1 { x: 2.0 }];
+
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .reset_limit_start(5000)
+            .reset_limit_end(1000)
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::LimitEndTooLow)),
+            Ok(_) => panic!("reset limit end lower than start accepted"),
+        }
+
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .keep_worst(2)
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::KeepWorstTooHigh)),
+            Ok(_) => panic!("keep_worst too high accepted"),
+        }
+
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .multi_parent_crossover(3)
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::MultiParentOutOfRange)),
+            Ok(_) => panic!("too many parents accepted"),
+        }
+
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::IndividualsTooLow)),
+            Ok(_) => panic!("empty population accepted"),
+        }
+
+        let result = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .eval_threads(0)
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::EvalThreadsTooLow)),
+            Ok(_) => panic!("zero eval threads accepted"),
         }
     }
 }
@@ -13,12 +13,21 @@
 //!
 //!
 
-use std::time::Instant;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, Write, BufWriter};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use jobsteal::make_pool;
+use log::LogLevel;
+use rand::{Rng, XorShiftRng};
 
-use individual::{Individual, IndividualWrapper};
-use population::Population;
+use individual::{Individual, IndividualWrapper, OptimizationGoal};
+use population::{Population, ConvergenceAction, PauseHandle, wait_while_all_paused};
+use metrics::{Metrics, MetricsSink};
+use util::weighted_index;
 
 /// The `SimulationType` type. Speficies the criteria on how a simulation should stop.
 #[derive(Debug,Clone)]
@@ -32,10 +41,268 @@ pub enum SimulationType {
     EndFitness(f64),
     /// Finish the simulation when a specific improvement factor is reached.
     /// That means the relation between the very first fitness and the current fitness of the
-    /// fittest individual.
+    /// fittest individual (see `OptimizationGoal::improvement_factor`).
     EndFactor(f64),
 }
 
+/// This trait can be implemented for user defined stop criteria, for example to stop when the
+/// improvement per iteration drops below a threshold or when a budget is used up.
+/// It is checked after every iteration in addition to the simulation type.
+pub trait StopCriterion: Send {
+    /// Returns true if the simulation should stop. `best` is the fitness of the fittest
+    /// individual found so far, `worst` is the fitness of the least fit individual in all
+    /// populations and `iteration` is the current iteration (starting with 1).
+    fn should_stop(&self, best: f64, worst: f64, iteration: u32) -> bool;
+}
+
+/// The `FitnessLimit` type. A stop criterion that stops the simulation when the fittest
+/// individual has reached the given fitness, just like `SimulationType::EndFitness`.
+#[derive(Debug,Clone)]
+pub struct FitnessLimit {
+    /// The fitness that has to be reached.
+    pub limit: f64,
+    /// Are lower or higher fitness values better ?
+    pub goal: OptimizationGoal,
+}
+
+impl FitnessLimit {
+    /// Creates a new stop criterion for the given fitness and optimization goal.
+    pub fn new(limit: f64, goal: OptimizationGoal) -> FitnessLimit {
+        FitnessLimit { limit, goal }
+    }
+}
+
+impl StopCriterion for FitnessLimit {
+    fn should_stop(&self, best: f64, _worst: f64, _iteration: u32) -> bool {
+        !self.goal.is_better(self.limit, best)
+    }
+}
+
+/// The `MigrationTopology` type. Specifies which populations (islands) exchange individuals
+/// when `share_fittest` is enabled.
+#[derive(Debug,Clone,PartialEq)]
+pub enum MigrationTopology {
+    /// Every population gets one of the global fittest individuals.
+    FullMesh,
+    /// The populations form a ring: each population gets the fittest individual of the
+    /// previous population.
+    Ring,
+    /// The populations form a grid (as square as possible, wrapping around at the borders):
+    /// each population gets the fittest individual of its best direct neighbor.
+    Grid,
+}
+
+/// The `ShareSelection` type. Specifies how the shared individual is chosen from the top
+/// `share_top_k` global fittest individuals.
+#[derive(Debug,Clone,PartialEq)]
+pub enum ShareSelection {
+    /// Cycle through the top individuals one after the other.
+    RoundRobin,
+    /// Choose randomly, weighted by fitness (fitness proportionate, roulette wheel).
+    /// The weights are calculated with the `fitness_scaling` of the simulation.
+    Roulette,
+    /// Choose randomly, weighted by rank: the best of the k individuals has weight k, the
+    /// next one k - 1 and so on.
+    Rank,
+}
+
+/// The `FitnessScaling` type. Specifies how the fitness values are turned into weights for
+/// fitness proportionate selection (`ShareSelection::Roulette`). This only affects the
+/// selection, the reported fitness values are not changed. For weights that only depend on
+/// the position use `ShareSelection::Rank` instead.
+#[derive(Debug,Clone,PartialEq)]
+pub enum FitnessScaling {
+    /// The weight is 1 / (1 + distance to the best fitness), so fitness values of zero work.
+    /// A single individual that is much better than all the others will be chosen nearly always.
+    None,
+    /// The distance to the best fitness is truncated at two standard deviations below the mean
+    /// (sigma truncation), individuals worse than that get a weight of zero.
+    SigmaTruncation,
+}
+
+impl FitnessScaling {
+    /// Returns the selection weights for the given fitness values, which must be sorted by
+    /// `goal` (best first). If all weights would be zero, every individual gets the same weight.
+    pub fn weights(&self, fitness: &[f64], goal: OptimizationGoal) -> Vec<f64> {
+        let best_fitness = match fitness.first() {
+            Some(best_fitness) => *best_fitness,
+            None => return Vec::new(),
+        };
+        // The distance to the best fitness, independent of the optimization goal.
+        let distances: Vec<f64> = fitness.iter()
+            .map(|value| goal.improvement(*value, best_fitness).abs())
+            .collect();
+
+        let weights: Vec<f64> = match *self {
+            FitnessScaling::None => distances.iter().map(|distance| 1.0 / (1.0 + distance)).collect(),
+            FitnessScaling::SigmaTruncation => {
+                let n = distances.len() as f64;
+                let mean = distances.iter().sum::<f64>() / n;
+                let sigma = (distances.iter().map(|distance| (distance - mean).powi(2)).sum::<f64>() / n).sqrt();
+                distances.iter().map(|distance| (mean + 2.0 * sigma - distance).max(0.0)).collect()
+            }
+        };
+
+        if weights.iter().any(|weight| *weight > 0.0) {
+            weights
+        } else {
+            vec![1.0; fitness.len()]
+        }
+    }
+}
+
+/// Returns the indices of the populations that send individuals to the population with the
+/// given index. For `FullMesh` this is empty, since the global fittest individuals are used.
+fn neighbors(topology: &MigrationTopology, index: usize, num_of_populations: usize) -> Vec<usize> {
+    let mut result = match *topology {
+        MigrationTopology::FullMesh => Vec::new(),
+        MigrationTopology::Ring => vec![(index + num_of_populations - 1) % num_of_populations],
+        MigrationTopology::Grid => {
+            let width = (num_of_populations as f64).sqrt().ceil() as usize;
+            let height = num_of_populations.div_ceil(width);
+            let (row, column) = (index / width, index % width);
+
+            vec![(row * width) + ((column + width - 1) % width),
+                 (row * width) + ((column + 1) % width),
+                 (((row + height - 1) % height) * width) + column,
+                 (((row + 1) % height) * width) + column]
+        }
+    };
+
+    result.retain(|&neighbor| (neighbor < num_of_populations) && (neighbor != index));
+    result.sort();
+    result.dedup();
+    result
+}
+
+/// The `PhaseConfig` type. Contains the parameters that will be changed when a phase of the
+/// simulation starts. All parameters are optional, only the ones that are set will be changed.
+/// This allows for example to explore with a big population and a high mutation rate at the
+/// beginning and to refine the result later with a small population and a low mutation rate.
+#[derive(Debug,Clone)]
+pub struct PhaseConfig {
+    /// The new number of individuals for each population.
+    pub num_of_individuals: Option<u32>,
+    /// The new number of mutations for every individual in each population.
+    pub num_of_mutations: Option<u32>,
+    /// Enable or disable sharing of the fittest individual between all populations.
+    pub share_fittest: Option<bool>,
+    /// The new weights for the objectives in each population.
+    pub weights: Option<Vec<f64>>,
+}
+
+impl Default for PhaseConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PhaseConfig {
+    /// Creates a new phase configuration that doesn't change anything.
+    pub fn new() -> PhaseConfig {
+        PhaseConfig {
+            num_of_individuals: None,
+            num_of_mutations: None,
+            share_fittest: None,
+            weights: None,
+        }
+    }
+
+    /// Change the number of individuals for each population when this phase starts.
+    pub fn num_of_individuals(mut self, num_of_individuals: u32) -> PhaseConfig {
+        self.num_of_individuals = Some(num_of_individuals);
+        self
+    }
+
+    /// Change the number of mutations for every individual when this phase starts.
+    pub fn num_of_mutations(mut self, num_of_mutations: u32) -> PhaseConfig {
+        self.num_of_mutations = Some(num_of_mutations);
+        self
+    }
+
+    /// Change the weights for the objectives when this phase starts.
+    pub fn weights(mut self, weights: Vec<f64>) -> PhaseConfig {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Enable or disable sharing of the fittest individual when this phase starts.
+    pub fn share_fittest(mut self, share_fittest: bool) -> PhaseConfig {
+        self.share_fittest = Some(share_fittest);
+        self
+    }
+}
+
+/// The `ProgressHandle` type. Allows another thread (for example a monitoring thread) to
+/// see the progress of a running simulation. Get one with `Simulation::progress_handle`
+/// before calling `run`. Reading the values never blocks the simulation.
+#[derive(Debug,Clone)]
+pub struct ProgressHandle {
+    best_fitness: Arc<AtomicU64>,
+    iteration: Arc<AtomicU32>,
+}
+
+impl Default for ProgressHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressHandle {
+    /// Creates a new handle with no progress yet.
+    pub fn new() -> ProgressHandle {
+        ProgressHandle {
+            best_fitness: Arc::new(AtomicU64::new(f64::MAX.to_bits())),
+            iteration: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Returns the fitness of the current fittest individual of the simulation.
+    pub fn current_best_fitness(&self) -> f64 {
+        f64::from_bits(self.best_fitness.load(Ordering::Relaxed))
+    }
+
+    /// Returns the current iteration of the simulation.
+    pub fn current_iteration(&self) -> u32 {
+        self.iteration.load(Ordering::Relaxed)
+    }
+
+    fn update(&self, best_fitness: f64, iteration: u32) {
+        self.best_fitness.store(best_fitness.to_bits(), Ordering::Relaxed);
+        self.iteration.store(iteration, Ordering::Relaxed);
+    }
+}
+
+/// The `StopHandle` type. Allows to stop a running simulation from another thread, for example
+/// from a signal handler when the user presses Ctrl-C. Get one with `Simulation::stop_handle`.
+/// The simulation stops gracefully after the current iteration: the result is kept and the
+/// fitness values are still written (see `SimulationBuilder::save_fitness_csv`).
+/// This is best effort: if the process is killed right away, nothing is saved.
+/// A stop request is cleared once the simulation has stopped, so `run` can be called again.
+#[derive(Debug,Clone)]
+pub struct StopHandle {
+    stop_requested: Arc<AtomicBool>,
+    /// Used to wake up the simulation if it is waiting for paused populations.
+    pause_handles: Vec<PauseHandle>,
+}
+
+impl StopHandle {
+    /// Asks the simulation to stop after the current iteration. If all populations are paused,
+    /// the simulation stops right away.
+    pub fn stop(&self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+
+        for pause_handle in &self.pause_handles {
+            pause_handle.wake();
+        }
+    }
+
+    /// Returns true if the simulation has been asked to stop and hasn't stopped yet.
+    pub fn is_stop_requested(&self) -> bool {
+        self.stop_requested.load(Ordering::SeqCst)
+    }
+}
+
 /// The `Simulation` type. Contains all the information / configuration for the simulation to run.
 /// Use the `SimulationBuilder` in order to create a simulation.
 pub struct Simulation<T: Individual + Send + Sync> {
@@ -72,7 +339,84 @@ pub struct Simulation<T: Individual + Send + Sync> {
     pub share_every: u32,
     /// Counter that will be incremented every iteration. If share_counter >= share_every then the
     /// most fittest individual is shared between all the populations.
-    pub share_counter: u32
+    pub share_counter: u32,
+    /// The number of global fittest individuals that are shared between the populations,
+    /// default: 1. If this is greater than one, the populations receive the top `share_top_k`
+    /// individuals of the "high score list" in a round robin fashion instead of all receiving
+    /// the same fittest individual. This way different populations explore different regions.
+    pub share_top_k: usize,
+    /// The position in the "high score list" of the next individual that will be shared.
+    pub share_cursor: usize,
+    /// The probability that a shared individual is accepted by a population even if it is
+    /// worse than the fittest individual of that population, default: 1.0.
+    /// A worse individual replaces the least fit individual of the population.
+    /// (Only usefull in combination with `share_top_k`).
+    pub accept_worse_prob: f64,
+    /// Re-calculate the fitness of all global fittest individuals every nth iteration.
+    /// This is useful if the fitness function is noisy: an individual with a lucky fitness
+    /// evaluation will be demoted once its fitness is re-calculated. Default: None (disabled).
+    pub reevaluate_best_every: Option<u32>,
+    /// The minimum improvement of the fitness that is needed to count as a new fittest
+    /// individual for the statistics, the log output, the `new_fittest_found` method, the
+    /// stagnation counter and the sharing of the fittest individuals.
+    /// The global fittest individuals are still updated on every improvement. Default: 0.0
+    pub new_best_epsilon: f64,
+    /// The fitness of the last individual that counted as a new fittest individual.
+    pub reported_fitness: f64,
+    /// The number of iterations without a new fittest individual after which the simulation
+    /// stops, regardless of the simulation type. Default: None (disabled).
+    pub stagnation_limit: Option<u32>,
+    /// The number of iterations since the last new fittest individual has been found.
+    pub stagnation_counter: u32,
+    /// The maximum (wall clock) run time of the simulation. If this is set, the simulation
+    /// stops when the time is up, regardless of the simulation type. Default: None (no limit).
+    pub max_run_time: Option<Duration>,
+    /// How the shared individual is chosen from the top `share_top_k` individuals.
+    /// Default: `ShareSelection::RoundRobin`.
+    pub share_selection: ShareSelection,
+    /// How the fitness values are turned into weights for `ShareSelection::Roulette`.
+    /// Default: `FitnessScaling::None`.
+    pub fitness_scaling: FitnessScaling,
+    /// Which populations exchange individuals, if `share_fittest` is enabled.
+    /// Default: `MigrationTopology::FullMesh`.
+    pub migration_topology: MigrationTopology,
+    /// Are lower or higher fitness values better ? This is also used by all populations.
+    /// Default: `OptimizationGoal::Minimize`.
+    pub goal: OptimizationGoal,
+    /// The random number generator of the simulation (sharing of individuals).
+    /// Use `SimulationBuilder::rng_seed` for reproducible runs.
+    pub rng: XorShiftRng,
+    /// The progress of the simulation, this can be read from another thread.
+    pub progress: ProgressHandle,
+    /// The phases of the simulation: each entry contains the iteration at which the phase starts
+    /// and the parameters that will be changed. The phases are sorted by start iteration.
+    pub phases: Vec<(u32, PhaseConfig)>,
+    /// The index of the next phase that will be activated.
+    pub next_phase: usize,
+    /// A user defined stop criterion that is checked after every iteration, regardless of
+    /// the simulation type. Default: None (disabled).
+    pub stop_criterion: Option<Box<dyn StopCriterion>>,
+    /// The maximum number of entries in the fitness history. Default: None (no history).
+    pub fitness_history_len: Option<usize>,
+    /// The fitness of the fittest individual after each iteration: (iteration, fitness).
+    /// If the history is full, the oldest entry is removed.
+    pub fitness_history: VecDeque<(u32, f64)>,
+    /// If this is set, the fitness of all individuals is written into this file (CSV) when the
+    /// simulation has finished, see `save_fitness_csv`. Default: None.
+    pub fitness_csv_path: Option<String>,
+    /// The number of global fittest individuals that are passed to the
+    /// `on_new_best_population` method of a new fittest individual. Default: 1
+    pub new_best_top_n: usize,
+    /// If this flag is set the simulation stops after the current iteration, the flag is
+    /// cleared again when the simulation stops. Use `stop_handle` to stop the simulation from another thread.
+    pub stop_requested: Arc<AtomicBool>,
+    /// The sum of the mutation statistics (see the `mutation_stats` method of the `Individual`
+    /// trait) of the fittest individual of each population, updated whenever a new fittest
+    /// individual is found.
+    pub mutation_stats: HashMap<String, u64>,
+    /// A user defined sink that receives the metrics after every iteration.
+    /// Default: None (disabled).
+    pub metrics_sink: Option<Box<dyn MetricsSink>>,
 }
 
 /// The `SimulationResult` Type. Holds the simulation results:
@@ -121,60 +465,198 @@ impl<T: Individual + Send + Sync + Clone> Simulation<T> {
             iteration_counter: 0
         };
 
+        self.reported_fitness = self.simulation_result.original_fitness;
+        self.fitness_history.clear();
+
         info!("original_fitness: {}", self.simulation_result.original_fitness);
 
-        // Check which type of simulation to run.
-        match self.type_of_simulation {
-            SimulationType::EndIteration(end_iteration) => {
-                for _ in 0..end_iteration {
-                    pool.scope(|scope|
-                        for population in &mut self.habitat {
-                            scope.submit(move || { population.run_body() });
-                        });
+        let pause_handles: Vec<PauseHandle> = self.habitat.iter().map(|population| population.pause_handle()).collect();
 
-                    self.update_results();
-                };
-                self.simulation_result.iteration_counter = end_iteration;
+        loop {
+            // Don't count iterations while all populations are paused.
+            wait_while_all_paused(&pause_handles, || self.stop_requested.load(Ordering::SeqCst));
+
+            // Stop if the user or another thread has asked for it, the request is consumed.
+            if self.stop_requested.swap(false, Ordering::SeqCst) {
+                info!("simulation stopped, stop requested, iteration: {}", iteration_counter);
+                break;
+            }
+
+            // Stop if the time limit is reached, the fittest individuals found so far are kept.
+            if let Some(max_run_time) = self.max_run_time {
+                if start_time.elapsed() >= max_run_time {
+                    info!("simulation stopped, time limit reached, iteration: {}", iteration_counter);
+                    break;
+                }
             }
 
-            SimulationType::EndFactor(end_factor) => {
-                loop {
-                    iteration_counter += 1;
-                    pool.scope(|scope|
-                        for population in &mut self.habitat {
-                            scope.submit(move || { population.run_body() });
-                        });
+            iteration_counter += 1;
 
-                    self.update_results();
+            // Switch to the next phase if its start iteration has been reached.
+            self.apply_phases(iteration_counter);
 
-                    if self.simulation_result.improvement_factor <= end_factor {
-                        break;
-                    }
-                };
-                self.simulation_result.iteration_counter = iteration_counter;
+            pool.scope(|scope|
+                for population in &mut self.habitat {
+                    scope.submit(move || { population.run_body() });
+                });
+
+            self.update_results();
+
+            if let Some(reevaluate_best_every) = self.reevaluate_best_every {
+                if iteration_counter % reevaluate_best_every == 0 {
+                    self.reevaluate_fittest();
+                }
             }
 
-            SimulationType::EndFitness(end_fitness) => {
-                loop {
-                    iteration_counter += 1;
-                    pool.scope(|scope|
-                        for population in &mut self.habitat {
-                            scope.submit(move || { population.run_body() });
-                        });
+            self.progress.update(self.simulation_result.fittest[0].fitness, iteration_counter);
 
-                    self.update_results();
+            let worst_fitness = self.worst_fitness();
+            let best = &self.simulation_result.fittest[0];
+            best.individual.on_iteration(iteration_counter, best.fitness, worst_fitness);
 
-                    if self.simulation_result.fittest[0].fitness <= end_fitness {
-                        break;
-                    }
-                };
-                self.simulation_result.iteration_counter = iteration_counter;
+            if let Some(fitness_history_len) = self.fitness_history_len {
+                if self.fitness_history.len() >= fitness_history_len {
+                    self.fitness_history.pop_front();
+                }
+                self.fitness_history.push_back((iteration_counter, self.simulation_result.fittest[0].fitness));
+            }
+
+            if let Some(ref metrics_sink) = self.metrics_sink {
+                metrics_sink.record(&self.metrics(iteration_counter));
+            }
+
+            // Check which type of simulation to run.
+            let finished = match self.type_of_simulation {
+                SimulationType::EndIteration(end_iteration) => {
+                    iteration_counter >= end_iteration
+                }
+                SimulationType::EndFactor(end_factor) => {
+                    self.simulation_result.improvement_factor <= end_factor
+                }
+                SimulationType::EndFitness(end_fitness) => {
+                    !self.goal.is_better(end_fitness, self.simulation_result.fittest[0].fitness)
+                }
+            };
+
+            // Stop if a population has fully converged and is configured to stop.
+            let converged = self.habitat.iter().any(|population|
+                population.converged && population.on_full_convergence == ConvergenceAction::Stop);
+
+            if converged {
+                info!("simulation stopped, population has converged, iteration: {}", iteration_counter);
+            }
+
+            // Stop if the fittest individual reports to be a solution.
+            let solved = {
+                let fittest = &self.simulation_result.fittest[0];
+                fittest.individual.is_solution(fittest.fitness)
+            };
+
+            if solved {
+                info!("simulation stopped, solution found, iteration: {}", iteration_counter);
+            }
+
+            // Stop if there hasn't been any improvement for too long.
+            let stagnated = match self.stagnation_limit {
+                Some(stagnation_limit) => self.stagnation_counter >= stagnation_limit,
+                None => false,
+            };
+
+            if stagnated {
+                info!("simulation stopped, no improvement for {} iterations, iteration: {}", self.stagnation_counter, iteration_counter);
+            }
+
+            // Stop if the user defined criterion is met.
+            let criterion_met = match self.stop_criterion {
+                Some(ref stop_criterion) => {
+                    stop_criterion.should_stop(self.simulation_result.fittest[0].fitness, self.worst_fitness(),
+                        iteration_counter)
+                }
+                None => false,
+            };
+
+            if criterion_met {
+                info!("simulation stopped, stop criterion met, iteration: {}", iteration_counter);
+            }
+
+            if finished || converged || solved || stagnated || criterion_met {
+                break;
+            }
+        }
+
+        self.simulation_result.iteration_counter = iteration_counter;
+
+        for population in &self.habitat {
+            info!("population id: {}, num_of_individuals: {}, fitness_std_dev: {}",
+                population.id, population.population.len(), population.fitness_std_dev());
+
+            // Both need O(n^2) calls of the `distance` method, so only calculate them if needed.
+            if log_enabled!(LogLevel::Debug) {
+                debug!("population id: {}, effective_population_size: {}, diversity: {}",
+                    population.id, population.effective_population_size(), population.diversity());
+            }
+        }
+
+        if let Some(ref path) = self.fitness_csv_path {
+            if let Err(error) = self.save_fitness_csv(path) {
+                error!("could not write fitness values to '{}': {}", path, error);
             }
-        } // End of match
+        }
 
         let elapsed = start_time.elapsed();
 
-        self.total_time_in_ms = elapsed.as_secs() as f64 * 1000.0 + elapsed.subsec_nanos() as f64 / 1000_000.0;
+        self.total_time_in_ms = elapsed.as_secs() as f64 * 1000.0 + elapsed.subsec_nanos() as f64 / 1_000_000.0;
+    }
+
+    /// Returns a handle that can be used to watch the progress of the simulation from another
+    /// thread while `run` is executing.
+    pub fn progress_handle(&self) -> ProgressHandle {
+        self.progress.clone()
+    }
+
+    /// Returns the metrics of all individuals of all populations for the given iteration:
+    /// the best, worst and median fitness and the mean diversity of the populations.
+    pub fn metrics(&self, iteration: u32) -> Metrics {
+        let mut all_individuals: Vec<&IndividualWrapper<T>> = self.habitat.iter()
+            .flat_map(|population| population.population.iter())
+            .collect();
+        all_individuals.sort_by(|a, b| self.goal.cmp(a, b));
+
+        let len = all_individuals.len();
+
+        let (best, worst, median) = if len == 0 {
+            (f64::NAN, f64::NAN, f64::NAN)
+        } else if len % 2 == 1 {
+            (all_individuals[0].fitness, all_individuals[len - 1].fitness, all_individuals[len / 2].fitness)
+        } else {
+            (all_individuals[0].fitness, all_individuals[len - 1].fitness,
+                (all_individuals[(len / 2) - 1].fitness + all_individuals[len / 2].fitness) / 2.0)
+        };
+
+        let diversity = self.habitat.iter().map(|population| population.diversity()).sum::<f64>() /
+            (self.habitat.len().max(1) as f64);
+
+        Metrics { iteration, best, worst, median, diversity }
+    }
+
+    /// Returns the fittest individual found so far or None if the simulation hasn't run yet.
+    pub fn best_individual(&self) -> Option<&T> {
+        self.simulation_result.fittest.first().map(|wrapper| &wrapper.individual)
+    }
+
+    /// Returns the fitness of the fittest individual found so far or None if the simulation
+    /// hasn't run yet.
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.simulation_result.fittest.first().map(|wrapper| wrapper.fitness)
+    }
+
+    /// Returns a handle that can be used to stop the simulation from another thread while
+    /// `run` is executing.
+    pub fn stop_handle(&self) -> StopHandle {
+        StopHandle {
+            stop_requested: self.stop_requested.clone(),
+            pause_handles: self.habitat.iter().map(|population| population.pause_handle()).collect(),
+        }
     }
 
     /// This is a helper function that the user can call after the simulation stops in order to
@@ -187,6 +669,199 @@ impl<T: Individual + Send + Sync + Clone> Simulation<T> {
         }
     }
 
+    /// Writes a short summary of all individuals of all populations into the given file.
+    /// This is a CSV file with one line per individual, sorted by fitness:
+    /// rank, fitness, population id and number of mutations.
+    /// The order of the lines is deterministic for the same individuals.
+    /// The individuals themselves are not written.
+    pub fn write_summary(&self, path: &str) -> io::Result<()> {
+        let mut all_individuals: Vec<&IndividualWrapper<T>> = self.habitat.iter()
+            .flat_map(|population| population.population.iter())
+            .collect();
+        // Break ties by population id and number of mutations, so that the file is the same
+        // regardless of the order of the individuals inside the populations.
+        all_individuals.sort_by(|a, b| self.goal.cmp(a, b)
+            .then(a.id.cmp(&b.id))
+            .then(a.num_of_mutations.cmp(&b.num_of_mutations)));
+
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writeln!(writer, "rank,fitness,population,num_of_mutations")?;
+
+        for (rank, wrapper) in all_individuals.iter().enumerate() {
+            writeln!(writer, "{},{},{},{}", rank, wrapper.fitness, wrapper.id, wrapper.num_of_mutations)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the fitness history into the given file as CSV, one line per iteration.
+    /// This can be used directly to plot the convergence of the simulation, for example
+    /// with gnuplot. The history must be enabled with `SimulationBuilder::fitness_history`.
+    pub fn save_fitness_history(&self, path: &str) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writeln!(writer, "iteration,fitness")?;
+
+        for &(iteration, fitness) in &self.fitness_history {
+            writeln!(writer, "{},{}", iteration, fitness)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the fitness of all individuals of all populations into the given file, one value
+    /// per line and ordered as the populations and individuals. In contrast to `write_summary`
+    /// only the fitness is written and the individuals are not sorted, which makes it easy to
+    /// create a histogram of the fitness values.
+    pub fn save_fitness_csv(&self, path: &str) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        for wrapper in self.habitat.iter().flat_map(|population| population.population.iter()) {
+            writeln!(writer, "{}", wrapper.fitness)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the score of each population into the given file as JSON: how often the
+    /// population has found a new fittest individual (`fitness_counter`) and when this
+    /// happened the last time (`last_improvement`, seconds since the unix epoch, or null).
+    /// The populations are sorted by their score, the best one first.
+    /// This helps to find populations (and parameters) that actually contribute.
+    pub fn save_population_scores(&self, path: &str) -> io::Result<()> {
+        let mut populations: Vec<&Population<T>> = self.habitat.iter().collect();
+        populations.sort_by(|a, b| b.fitness_counter.cmp(&a.fitness_counter).then(a.id.cmp(&b.id)));
+
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writeln!(writer, "[")?;
+
+        for (index, population) in populations.iter().enumerate() {
+            let last_improvement = match population.last_improvement {
+                Some(time) => time.duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs().to_string())
+                    .unwrap_or_else(|_| "null".to_string()),
+                None => "null".to_string(),
+            };
+            let separator = if index + 1 < populations.len() { "," } else { "" };
+
+            writeln!(writer, "  {{\"id\": {}, \"fitness_counter\": {}, \"last_improvement\": {}}}{}",
+                population.id, population.fitness_counter, last_improvement, separator)?;
+        }
+
+        writeln!(writer, "]")?;
+
+        Ok(())
+    }
+
+    /// Activate all phases whose start iteration has been reached and apply their parameters
+    /// to all populations.
+    fn apply_phases(&mut self, iteration_counter: u32) {
+        while self.next_phase < self.phases.len() && self.phases[self.next_phase].0 <= iteration_counter {
+            let phase = self.phases[self.next_phase].1.clone();
+            info!("new phase: {}, iteration: {}", self.next_phase, iteration_counter);
+
+            for population in &mut self.habitat {
+                if let Some(num_of_individuals) = phase.num_of_individuals {
+                    population.resize(num_of_individuals);
+                }
+                if let Some(num_of_mutations) = phase.num_of_mutations {
+                    population.set_num_of_mutations(num_of_mutations);
+                }
+                if let Some(ref weights) = phase.weights {
+                    population.set_weights(weights.clone());
+                }
+            }
+
+            if let Some(share_fittest) = phase.share_fittest {
+                self.share_fittest = share_fittest;
+            }
+
+            self.next_phase += 1;
+        }
+    }
+
+    /// Adds up the mutation statistics of the fittest individual of each population.
+    /// Populations whose individuals don't provide any statistics are ignored.
+    fn update_mutation_stats(&mut self) {
+        self.mutation_stats.clear();
+
+        for population in &self.habitat {
+            if let Some(stats) = population.population[0].individual.mutation_stats() {
+                for (operator, count) in stats {
+                    *self.mutation_stats.entry(operator).or_insert(0) += count;
+                }
+            }
+        }
+
+        if !self.mutation_stats.is_empty() {
+            let mut stats: Vec<(&String, &u64)> = self.mutation_stats.iter().collect();
+            stats.sort();
+            info!("mutation stats: {:?}", stats);
+        }
+    }
+
+    /// Re-calculates the fitness of all the global fittest individuals and sorts them again.
+    /// The fitness is calculated by the population the individual belongs to.
+    fn reevaluate_fittest(&mut self) {
+        for wrapper in &mut self.simulation_result.fittest {
+            wrapper.fitness = match self.habitat.iter().find(|population| population.id == wrapper.id) {
+                Some(population) => population.evaluate(&mut wrapper.individual),
+                None => wrapper.individual.calculate_fitness(),
+            };
+        }
+
+        self.goal.sort(&mut self.simulation_result.fittest);
+        self.reported_fitness = self.simulation_result.fittest[0].fitness;
+    }
+
+    /// Returns the next individual that will be shared with a population. This cycles through
+    /// the top `share_top_k` individuals of the global fittest list, or chooses one of them
+    /// randomly, depending on `share_selection`.
+    fn next_shared_individual(&mut self) -> IndividualWrapper<T> {
+        let top_k = self.share_top_k.min(self.simulation_result.fittest.len()).max(1);
+        let index = match self.share_selection {
+            ShareSelection::RoundRobin => {
+                let index = self.share_cursor % top_k;
+                self.share_cursor = (index + 1) % top_k;
+                index
+            }
+            ShareSelection::Roulette | ShareSelection::Rank => {
+                let weights = self.share_weights(top_k);
+                weighted_index(&mut self.rng, &weights)
+            }
+        };
+        self.simulation_result.fittest[index].clone()
+    }
+
+    /// Returns the selection weights of the top `top_k` global fittest individuals for
+    /// `ShareSelection::Roulette` (scaled with `fitness_scaling`) and `ShareSelection::Rank`.
+    /// With `ShareSelection::RoundRobin` all individuals have the same weight.
+    fn share_weights(&self, top_k: usize) -> Vec<f64> {
+        match self.share_selection {
+            ShareSelection::RoundRobin => vec![1.0; top_k],
+            ShareSelection::Roulette => {
+                let fitness: Vec<f64> = self.simulation_result.fittest[..top_k].iter()
+                    .map(|wrapper| wrapper.fitness)
+                    .collect();
+                self.fitness_scaling.weights(&fitness, self.goal)
+            }
+            ShareSelection::Rank => (0..top_k).map(|rank| (top_k - rank) as f64).collect(),
+        }
+    }
+
+    /// Returns the worst fitness of all individuals of all populations, or the fitness of the
+    /// global fittest individual if it is worse.
+    fn worst_fitness(&self) -> f64 {
+        let goal = self.goal;
+        self.habitat.iter()
+            .flat_map(|population| population.population.iter())
+            .map(|wrapper| wrapper.fitness)
+            .fold(self.simulation_result.fittest[0].fitness,
+                |worst, fitness| if goal.is_better(worst, fitness) { fitness } else { worst })
+    }
+
     /// Update the internal state of the simulation: Has a new fittest individual been found ?
     /// Do we want to share it across all the other populations ?
     /// Also calculates the improvement factor.
@@ -199,36 +874,1186 @@ impl<T: Individual + Send + Sync + Clone> Simulation<T> {
         self.output_every_counter += 1;
 
         for population in &mut self.habitat {
-            if population.population[0].fitness < self.simulation_result.fittest[0].fitness {
-                new_fittest_found = true;
+            if self.goal.is_better(population.population[0].fitness, self.simulation_result.fittest[0].fitness) {
                 self.simulation_result.fittest.insert(0, population.population[0].clone());
                 // See https://github.com/willi-kappler/darwin-rs/issues/12
                 self.simulation_result.fittest.truncate(self.num_of_global_fittest);
+
+                // Only count the improvement if it is big enough.
+                if self.goal.improvement(self.reported_fitness, population.population[0].fitness) < self.new_best_epsilon {
+                    continue;
+                }
+                self.reported_fitness = population.population[0].fitness;
+                new_fittest_found = true;
+
                 population.fitness_counter += 1;
+                population.last_improvement = Some(SystemTime::now());
                 if self.output_every_counter >= self.output_every {
-                    info!("new fittest: fitness: {}, population id: {}, counter: {}", population.population[0].fitness,population.id,
-                        population.fitness_counter);
+                    info!("new fittest: fitness: {}, population id: {}, counter: {}, fitness_std_dev: {}, improvement_factor: {}",
+                        population.population[0].fitness, population.id, population.fitness_counter, population.fitness_std_dev(),
+                        self.goal.improvement_factor(self.simulation_result.original_fitness, population.population[0].fitness));
                     self.output_every_counter = 0
                 }
                 // Call methond `new_fittest_found` of the newly found fittest individual.
                 // The default implementation for this method does nothing.
                 population.population[0].individual.new_fittest_found();
+
+                let top_n = self.new_best_top_n.min(self.simulation_result.fittest.len());
+                let others: Vec<&T> = self.simulation_result.fittest[..top_n].iter()
+                    .map(|wrapper| &wrapper.individual)
+                    .collect();
+                population.population[0].individual.on_new_best_population(&others);
             }
         }
 
+        if new_fittest_found {
+            self.stagnation_counter = 0;
+            self.update_mutation_stats();
+        } else {
+            self.stagnation_counter += 1;
+        }
+
         // Now copy the most fittest individual back to each population
         // if the user has specified it and the share_every count is reached
         self.share_counter += 1;
         if self.share_fittest && new_fittest_found && (self.share_counter >= self.share_every) {
-            for population in &mut self.habitat {
-                population.population[0] = self.simulation_result.fittest[0].clone();
+            // The fittest individual of each population, before any of them is changed.
+            let local_fittest: Vec<IndividualWrapper<T>> = self.habitat.iter()
+                .map(|population| population.population[0].clone())
+                .collect();
+
+            for i in 0..self.habitat.len() {
+                let shared = if self.migration_topology == MigrationTopology::FullMesh {
+                    self.next_shared_individual()
+                } else {
+                    let goal = self.goal;
+                    match neighbors(&self.migration_topology, i, self.habitat.len()).into_iter()
+                        .map(|neighbor| &local_fittest[neighbor])
+                        .min_by(|wrapper1, wrapper2| goal.cmp(wrapper1, wrapper2)) {
+                        Some(wrapper) => wrapper.clone(),
+                        None => continue,
+                    }
+                };
+                let accept_worse = self.rng.gen::<f64>() < self.accept_worse_prob;
+                self.habitat[i].insert_shared(shared, accept_worse);
             }
             self.share_counter = 0;
         }
 
-        self.simulation_result.improvement_factor =
-            self.simulation_result.fittest[0].fitness /
-            self.simulation_result.original_fitness;
+        self.simulation_result.improvement_factor = self.goal.improvement_factor(
+            self.simulation_result.original_fitness, self.simulation_result.fittest[0].fitness);
+
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, VecDeque};
+    use std::env;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::fs::{self, File};
+    use std::io::Read;
+    use std::thread;
+    use std::time::{Duration, Instant, UNIX_EPOCH};
+
+    use super::{Simulation, SimulationType, PhaseConfig, MigrationTopology, ShareSelection, FitnessScaling,
+        StopCriterion, FitnessLimit, neighbors};
+    use individual::IndividualWrapper;
+    use individual::{Individual, OptimizationGoal};
+    use population::{RestartStrategy, ConvergenceAction};
+    use population_builder::PopulationBuilder;
+    use simulation_builder::{SimulationBuilder, ErrorKind};
+    use metrics::{Metrics, MetricsSink};
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest1 {
+        x: i64,
+    }
+
+    impl Individual for IndividualTest1 {
+        fn mutate(&mut self) {
+            self.x -= 1;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x.abs() as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 100;
+        }
+    }
+
+    fn make_simulation(builder: SimulationBuilder<IndividualTest1>) -> Simulation<IndividualTest1> {
+        let individuals: Vec<IndividualTest1> = (0..10).map(|i| IndividualTest1 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .finalize().unwrap();
+
+        builder.add_population(population).finalize().unwrap()
+    }
+
+    #[test]
+    fn phases1() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .iterations(20)
+            .add_phase(15, PhaseConfig::new().num_of_individuals(3).num_of_mutations(1))
+            .add_phase(5, PhaseConfig::new().num_of_individuals(20).num_of_mutations(4).share_fittest(true)));
+
+        simulation.apply_phases(4);
+        assert_eq!(simulation.habitat[0].population.len(), 10);
+        assert_eq!(simulation.habitat[0].population[0].num_of_mutations, 1);
+        assert!(!simulation.share_fittest);
+
+        simulation.apply_phases(5);
+        assert_eq!(simulation.habitat[0].population.len(), 20);
+        assert_eq!(simulation.habitat[0].num_of_individuals, 20);
+        assert_eq!(simulation.habitat[0].population[19].num_of_mutations, 4);
+        assert!(simulation.share_fittest);
+
+        simulation.apply_phases(14);
+        assert_eq!(simulation.habitat[0].population.len(), 20);
+
+        simulation.apply_phases(15);
+        assert_eq!(simulation.habitat[0].population.len(), 3);
+        assert_eq!(simulation.habitat[0].population[0].num_of_mutations, 1);
+    }
+
+    #[test]
+    fn share_top_k1() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .share_top_k(3));
+
+        simulation.simulation_result.fittest = (0..5).map(|x| IndividualWrapper {
+            individual: IndividualTest1 { x },
+            fitness: x as f64,
+            num_of_mutations: 1,
+            id: 1,
+        }).collect();
+
+        let shared: Vec<i64> = (0..7).map(|_| simulation.next_shared_individual().individual.x).collect();
+
+        assert_eq!(shared, vec![0, 1, 2, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn reevaluate_fittest1() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .reevaluate_best_every(5));
+
+        // The first individual had a lucky fitness evaluation.
+        simulation.simulation_result.fittest = vec![
+            IndividualWrapper { individual: IndividualTest1 { x: 50 }, fitness: 0.5, num_of_mutations: 1, id: 1 },
+            IndividualWrapper { individual: IndividualTest1 { x: 10 }, fitness: 10.0, num_of_mutations: 1, id: 1 },
+        ];
+
+        simulation.reevaluate_fittest();
+
+        assert_eq!(simulation.simulation_result.fittest[0].individual.x, 10);
+        assert_eq!(simulation.simulation_result.fittest[1].individual.x, 50);
+        assert_eq!(simulation.simulation_result.fittest[1].fitness, 50.0);
+    }
+
+    #[test]
+    fn new_best_epsilon1() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .new_best_epsilon(1.0));
+
+        simulation.simulation_result.fittest = vec![
+            IndividualWrapper { individual: IndividualTest1 { x: 10 }, fitness: 10.0, num_of_mutations: 1, id: 1 },
+        ];
+        simulation.reported_fitness = 10.0;
+
+        for i in 1..11 {
+            simulation.habitat[0].population[0].fitness = 10.0 - (0.4 * i as f64);
+            simulation.update_results();
+        }
+
+        // Only every third improvement adds up to more than 1.0
+        assert_eq!(simulation.habitat[0].fitness_counter, 3);
+        // The last improvement is too small, so the simulation is stagnating.
+        assert_eq!(simulation.stagnation_counter, 1);
+        assert!((simulation.simulation_result.fittest[0].fitness - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn write_summary1() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new());
+
+        simulation.habitat[0].calculate_fitness();
+
+        let path = env::temp_dir().join("darwin_rs_write_summary1.csv");
+        simulation.write_summary(path.to_str().unwrap()).unwrap();
+
+        let mut content = String::new();
+        File::open(&path).unwrap().read_to_string(&mut content).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let lines: Vec<&str> = content.lines().collect();
+
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "rank,fitness,population,num_of_mutations");
+        assert_eq!(lines[1], "0,100,1,1");
+        assert_eq!(lines[10], "9,109,1,1");
+    }
+
+    #[test]
+    fn restart_strategy1() {
+        let individuals: Vec<IndividualTest1> = (0..10).map(|i| IndividualTest1 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .restart_strategy(RestartStrategy::FixedInterval { rounds: 5 })
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(12)
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        // The population has just been restarted, but the fittest individual is kept.
+        assert_eq!(simulation.habitat[0].num_of_restarts, 2);
+        assert_eq!(simulation.habitat[0].population[0].fitness, 99.0);
+        assert_eq!(simulation.simulation_result.fittest[0].fitness, 94.0);
+    }
+
+    #[test]
+    fn write_summary2() {
+        let individuals: Vec<IndividualTest1> = (0..10).map(|i| IndividualTest1 { x: 100 + (i % 3) }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .increasing_mutation_rate()
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.habitat[0].calculate_fitness();
+
+        let mut read_summary = |name: &str| {
+            let path = env::temp_dir().join(name);
+            simulation.write_summary(path.to_str().unwrap()).unwrap();
+
+            let mut content = Vec::new();
+            File::open(&path).unwrap().read_to_end(&mut content).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            // Same individuals, different order for the next call.
+            simulation.habitat[0].population.reverse();
+            simulation.habitat[0].population.swap(0, 3);
+
+            content
+        };
+
+        let content1 = read_summary("darwin_rs_write_summary2a.csv");
+        let content2 = read_summary("darwin_rs_write_summary2b.csv");
+        let content3 = read_summary("darwin_rs_write_summary2c.csv");
+
+        assert_eq!(content1, content2);
+        assert_eq!(content1, content3);
+    }
+
+    #[test]
+    fn accept_worse_prob1() {
+        for &(accept_worse_prob, expected) in &[(1.0, true), (0.0, false)] {
+            let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+                .share_fittest()
+                .share_every(1)
+                .share_top_k(2)
+                .accept_worse_prob(accept_worse_prob));
+
+            simulation.habitat[0].calculate_fitness();
+            simulation.habitat[0].population.sort();
+
+            simulation.simulation_result.fittest = vec![
+                IndividualWrapper { individual: IndividualTest1 { x: 200 }, fitness: 200.0, num_of_mutations: 1, id: 1 },
+                IndividualWrapper { individual: IndividualTest1 { x: 500 }, fitness: 500.0, num_of_mutations: 1, id: 1 },
+            ];
+            simulation.share_cursor = 1;
+
+            // The population finds a new fittest individual (100) and gets the second
+            // best (200) in exchange.
+            simulation.update_results();
+
+            let population = &simulation.habitat[0].population;
+
+            assert_eq!(population[0].individual.x, 100);
+            assert_eq!(population.iter().any(|wrapper| wrapper.individual.x == 200), expected);
+        }
+    }
+
+    #[test]
+    fn progress_handle1() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .iterations(20));
+
+        let handle = simulation.progress_handle();
+
+        assert_eq!(handle.current_iteration(), 0);
+        assert_eq!(handle.current_best_fitness(), f64::MAX);
+
+        let monitor = thread::spawn(move || {
+            let mut best_fitness = Vec::new();
+            while handle.current_iteration() < 20 {
+                best_fitness.push(handle.current_best_fitness());
+                thread::yield_now();
+            }
+            best_fitness.push(handle.current_best_fitness());
+            best_fitness
+        });
+
+        simulation.run();
+
+        let best_fitness = monitor.join().unwrap();
+
+        // The polled fitness never gets worse and ends with the result of the simulation.
+        assert!(best_fitness.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert_eq!(*best_fitness.last().unwrap(), simulation.simulation_result.fittest[0].fitness);
+    }
+
+    #[test]
+    fn phases2() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .iterations(20)
+            .add_phase(10, PhaseConfig::new().num_of_individuals(5)));
+
+        simulation.run();
+
+        assert_eq!(simulation.simulation_result.iteration_counter, 20);
+        assert_eq!(simulation.habitat[0].population.len(), 5);
+    }
+
+    #[test]
+    fn on_full_convergence1() {
+        let individuals: Vec<IndividualTest1> = (0..10).map(|_| IndividualTest1 { x: 0 }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .on_full_convergence(ConvergenceAction::Stop)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(100)
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        assert_eq!(simulation.simulation_result.iteration_counter, 1);
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest2 {
+        x: i64,
+    }
+
+    impl Individual for IndividualTest2 {
+        fn mutate(&mut self) {
+            self.x -= 1;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            (self.x.abs() + 1000) as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 100;
+        }
+
+        fn is_solution(&self, _fitness: f64) -> bool {
+            self.x <= 95
+        }
+    }
+
+    #[test]
+    fn is_solution1() {
+        let individuals: Vec<IndividualTest2> = (0..10).map(|i| IndividualTest2 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest2>::new()
+            .initial_population(&individuals)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest2>::new()
+            .iterations(1000)
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        // Stops as soon as the fittest individual is a solution, even with a high fitness.
+        assert_eq!(simulation.simulation_result.iteration_counter, 5);
+        assert_eq!(simulation.simulation_result.fittest[0].fitness, 1095.0);
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest5 {
+        x: i64,
+    }
+
+    impl Individual for IndividualTest5 {
+        fn mutate(&mut self) {
+            self.x -= 1;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x.abs() as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 10;
+        }
+
+        fn is_solution(&self, fitness: f64) -> bool {
+            fitness == 0.0
+        }
+    }
+
+    #[test]
+    fn is_solution3() {
+        let individuals: Vec<IndividualTest5> = (0..10).map(|i| IndividualTest5 { x: 5 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest5>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest5>::new()
+            .iterations(1000)
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        // Stops the moment an individual with zero fitness appears.
+        assert_eq!(simulation.simulation_result.iteration_counter, 5);
+        assert_eq!(simulation.simulation_result.fittest[0].fitness, 0.0);
+        assert!(simulation.habitat[0].is_solved());
+
+        // The solved population only counts the iterations.
+        let iteration_counter = simulation.habitat[0].iteration_counter;
+        simulation.habitat[0].run_body();
+
+        assert_eq!(simulation.habitat[0].iteration_counter, iteration_counter + 1);
+        assert_eq!(simulation.habitat[0].population[0].fitness, 0.0);
+    }
+
+    #[test]
+    fn is_solution2() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .iterations(20));
+
+        simulation.run();
+
+        assert_eq!(simulation.simulation_result.iteration_counter, 20);
+    }
+
+    static ON_ITERATION_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest6 {
+        x: i64,
+    }
+
+    impl Individual for IndividualTest6 {
+        fn mutate(&mut self) {
+            self.x -= 1;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x.abs() as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 100;
+        }
+
+        fn on_iteration(&self, iteration: u32, best_fitness: f64, worst_fitness: f64) {
+            assert!(iteration > 0);
+            assert!(best_fitness <= worst_fitness);
+            ON_ITERATION_CALLS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn on_iteration1() {
+        let individuals: Vec<IndividualTest6> = (0..10).map(|i| IndividualTest6 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest6>::new()
+            .initial_population(&individuals)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest6>::new()
+            .iterations(20)
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        assert_eq!(ON_ITERATION_CALLS.load(Ordering::SeqCst), 20);
+    }
+
+    #[test]
+    fn goal1() {
+        let individuals: Vec<IndividualTest1> = (0..10).map(|i| IndividualTest1 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .fitness(105.0)
+            .goal(OptimizationGoal::Maximize)
+            .add_population(population)
+            .finalize().unwrap();
+
+        assert_eq!(simulation.habitat[0].goal, OptimizationGoal::Maximize);
+
+        simulation.run();
+
+        // With maximization the initial best individual (x = 109) already reaches the limit.
+        assert_eq!(simulation.simulation_result.iteration_counter, 1);
+        assert_eq!(simulation.simulation_result.fittest[0].fitness, 109.0);
+    }
+
+    #[test]
+    fn goal2() {
+        let individuals: Vec<IndividualTest1> = (0..10).map(|i| IndividualTest1 { x: -100 - i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .factor(0.5)
+            .goal(OptimizationGoal::Maximize)
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        // The first individual has a fitness of 100, the fittest one improves from 109 by one
+        // in every iteration, until it reaches 200.
+        assert_eq!(simulation.simulation_result.iteration_counter, 91);
+        assert_eq!(simulation.simulation_result.improvement_factor, 0.5);
+    }
+
+    #[test]
+    fn max_run_time1() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .iterations(u32::MAX)
+            .max_run_time(Duration::from_millis(50)));
+
+        let start_time = Instant::now();
+
+        simulation.run();
+
+        assert!(start_time.elapsed() < Duration::from_secs(10));
+        assert!(simulation.simulation_result.iteration_counter > 0);
+        assert!(simulation.simulation_result.iteration_counter < u32::MAX);
+        assert!(simulation.simulation_result.fittest[0].fitness < 100.0);
+    }
+
+    #[test]
+    fn max_run_time2() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .iterations(100)
+            .max_run_time(Duration::from_millis(0)));
+
+        simulation.run();
+
+        // No iteration at all, but the fittest individual of the initial population is kept.
+        assert_eq!(simulation.simulation_result.iteration_counter, 0);
+        assert_eq!(simulation.simulation_result.fittest[0].fitness, 100.0);
+    }
+
+    #[test]
+    fn stagnation_limit1() {
+        let individuals: Vec<IndividualTest1> = (0..10).map(|i| IndividualTest1 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(1000)
+            .stagnation_limit(5)
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        // The optimum is found in iteration 100, after that there is no improvement.
+        assert_eq!(simulation.simulation_result.fittest[0].fitness, 0.0);
+        assert_eq!(simulation.simulation_result.iteration_counter, 105);
+    }
+
+    struct WorstLimit {
+        limit: f64,
+    }
+
+    impl StopCriterion for WorstLimit {
+        fn should_stop(&self, _best: f64, worst: f64, _iteration: u32) -> bool {
+            worst <= self.limit
+        }
+    }
+
+    #[test]
+    fn stop_criterion1() {
+        let individuals: Vec<IndividualTest1> = (0..10).map(|i| IndividualTest1 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(1000)
+            .stop_criterion(WorstLimit { limit: 50.0 })
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        assert!(simulation.simulation_result.iteration_counter < 1000);
+        assert!(simulation.habitat[0].population.iter().all(|wrapper| wrapper.fitness <= 50.0));
+    }
+
+    #[test]
+    fn stop_criterion2() {
+        let individuals: Vec<IndividualTest1> = (0..10).map(|i| IndividualTest1 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(1000)
+            .stop_criterion(FitnessLimit::new(40.0, OptimizationGoal::Minimize))
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        // The fittest individual improves by one in every iteration.
+        assert_eq!(simulation.simulation_result.fittest[0].fitness, 40.0);
+        assert_eq!(simulation.simulation_result.iteration_counter, 60);
+    }
+
+    #[test]
+    fn stagnation_limit2() {
+        let result = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(100)
+            .stagnation_limit(0)
+            .finalize();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn finalize1() {
+        let result = SimulationBuilder::<IndividualTest1>::new()
+            .fitness(f64::NAN)
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::FitnessNotFinite)),
+            Ok(_) => panic!("NaN fitness limit accepted"),
+        }
+
+        let result = SimulationBuilder::<IndividualTest1>::new()
+            .factor(f64::INFINITY)
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::FactorNotFinite)),
+            Ok(_) => panic!("infinite factor accepted"),
+        }
+
+        assert!(make_simulation(SimulationBuilder::<IndividualTest1>::new().fitness(0.5)).habitat.len() == 1);
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&[IndividualTest1 { x: 1 }])
+            .finalize().unwrap();
+
+        let result = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(100)
+            .accept_worse_prob(1.5)
+            .add_population(population.clone())
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::AcceptWorseProbOutOfRange)),
+            Ok(_) => panic!("probability above 1.0 accepted"),
+        }
+
+        let result = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(100)
+            .new_best_epsilon(-1.0)
+            .add_population(population)
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::NewBestEpsilonTooLow)),
+            Ok(_) => panic!("negative epsilon accepted"),
+        }
+    }
+
+    #[test]
+    fn finalize2() {
+        let result = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(100)
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::NoPopulations)),
+            Ok(_) => panic!("simulation without populations accepted"),
+        }
+    }
+
+    #[test]
+    fn neighbors1() {
+        assert_eq!(neighbors(&MigrationTopology::FullMesh, 0, 4), Vec::<usize>::new());
+        assert_eq!(neighbors(&MigrationTopology::Ring, 0, 4), vec![3]);
+        assert_eq!(neighbors(&MigrationTopology::Ring, 2, 4), vec![1]);
+        assert_eq!(neighbors(&MigrationTopology::Ring, 0, 1), Vec::<usize>::new());
+
+        // 0 1 2
+        // 3 4 5
+        assert_eq!(neighbors(&MigrationTopology::Grid, 0, 6), vec![1, 2, 3]);
+        assert_eq!(neighbors(&MigrationTopology::Grid, 4, 6), vec![1, 3, 5]);
+
+        // 0 1 2
+        // 3 4
+        assert_eq!(neighbors(&MigrationTopology::Grid, 4, 5), vec![1, 3]);
+        assert_eq!(neighbors(&MigrationTopology::Grid, 2, 5), vec![0, 1]);
+    }
+
+    #[test]
+    fn migration_topology1() {
+        let populations: Vec<_> = (0..3).map(|k| {
+            let individuals: Vec<IndividualTest1> = (0..3).map(|i| IndividualTest1 { x: (10 * k) + i }).collect();
+
+            let mut population = PopulationBuilder::<IndividualTest1>::new()
+                .initial_population(&individuals)
+                .set_id(k as u32)
+                .finalize().unwrap();
+
+            population.calculate_fitness();
+            population
+        }).collect();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .share_fittest()
+            .share_every(1)
+            .migration_topology(MigrationTopology::Ring)
+            .add_multiple_populations(populations)
+            .finalize().unwrap();
+
+        simulation.simulation_result.fittest = vec![
+            IndividualWrapper { individual: IndividualTest1 { x: 1000 }, fitness: 1000.0, num_of_mutations: 1, id: 1 }];
+
+        simulation.update_results();
+
+        // Each population gets the fittest individual of the previous one in the ring.
+        assert!(simulation.habitat[0].population.iter().any(|wrapper| wrapper.individual.x == 20));
+        assert_eq!(simulation.habitat[1].population[0].individual.x, 0);
+        assert_eq!(simulation.habitat[2].population[0].individual.x, 10);
+    }
+
+    #[test]
+    fn share_selection1() {
+        for share_selection in [ShareSelection::Roulette, ShareSelection::Rank] {
+            let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+                .share_top_k(3)
+                .share_selection(share_selection)
+                .rng_seed(3));
+
+            simulation.simulation_result.fittest = (0..5).map(|x| IndividualWrapper {
+                individual: IndividualTest1 { x },
+                fitness: x as f64,
+                num_of_mutations: 1,
+                id: 1,
+            }).collect();
+
+            let mut counts = [0; 5];
+            for _ in 0..6000 {
+                counts[simulation.next_shared_individual().individual.x as usize] += 1;
+            }
+
+            // Only the top three are shared and the better ones are shared more often.
+            assert!(counts[0] > counts[1]);
+            assert!(counts[1] > counts[2]);
+            assert!(counts[2] > 0);
+            assert_eq!(counts[3] + counts[4], 0);
+        }
+    }
+
+    #[test]
+    fn save_population_scores1() {
+        let populations: Vec<_> = (1..4).map(|id| {
+            let individuals: Vec<IndividualTest1> = (0..3).map(|i| IndividualTest1 { x: i }).collect();
+
+            PopulationBuilder::<IndividualTest1>::new()
+                .initial_population(&individuals)
+                .set_id(id)
+                .finalize().unwrap()
+        }).collect();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .add_multiple_populations(populations)
+            .finalize().unwrap();
+
+        simulation.habitat[1].fitness_counter = 5;
+        simulation.habitat[1].last_improvement = Some(UNIX_EPOCH + Duration::from_secs(1_500_000_000));
+        simulation.habitat[2].fitness_counter = 2;
+        simulation.habitat[2].last_improvement = Some(UNIX_EPOCH + Duration::from_secs(1_400_000_000));
+
+        let path = env::temp_dir().join("darwin_rs_save_population_scores1.json");
+        simulation.save_population_scores(path.to_str().unwrap()).unwrap();
+
+        let mut content = String::new();
+        File::open(&path).unwrap().read_to_string(&mut content).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(content, "[\n\
+            \x20 {\"id\": 2, \"fitness_counter\": 5, \"last_improvement\": 1500000000},\n\
+            \x20 {\"id\": 3, \"fitness_counter\": 2, \"last_improvement\": 1400000000},\n\
+            \x20 {\"id\": 1, \"fitness_counter\": 0, \"last_improvement\": null}\n\
+            ]\n");
+    }
+
+    #[test]
+    fn fitness_history1() {
+        let individuals: Vec<IndividualTest1> = (0..10).map(|i| IndividualTest1 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(20)
+            .fitness_history(3)
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        assert_eq!(simulation.fitness_history, VecDeque::from(vec![(18, 82.0), (19, 81.0), (20, 80.0)]));
+
+        let path = env::temp_dir().join("darwin_rs_fitness_history1.csv");
+        simulation.save_fitness_history(path.to_str().unwrap()).unwrap();
+
+        let mut content = String::new();
+        File::open(&path).unwrap().read_to_string(&mut content).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(content, "iteration,fitness\n18,82\n19,81\n20,80\n");
+    }
+
+    #[test]
+    fn fitness_history2() {
+        let result = SimulationBuilder::<IndividualTest1>::new()
+            .fitness_history(0)
+            .finalize();
+
+        match result {
+            Err(error) => assert!(matches!(*error.kind(), ErrorKind::FitnessHistoryTooShort)),
+            Ok(_) => panic!("empty fitness history accepted"),
+        }
+    }
+
+    #[test]
+    fn save_fitness_csv1() {
+        let path = env::temp_dir().join("darwin_rs_save_fitness_csv1.csv");
+        let individuals: Vec<IndividualTest1> = (0..3).map(|i| IndividualTest1 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(10)
+            .save_fitness_csv(path.to_str().unwrap())
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        let mut content = String::new();
+        File::open(&path).unwrap().read_to_string(&mut content).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let fitness: Vec<f64> = content.lines().map(|line| line.parse().unwrap()).collect();
+        let expected: Vec<f64> = simulation.habitat[0].population.iter().map(|wrapper| wrapper.fitness).collect();
+
+        assert_eq!(fitness, expected);
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest3 {
+        x: i64,
+        reported: Arc<Mutex<Vec<Vec<i64>>>>,
+    }
+
+    impl Individual for IndividualTest3 {
+        fn mutate(&mut self) {
+            self.x -= 1;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x.abs() as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 100;
+        }
+
+        fn on_new_best_population(&self, others: &[&Self]) {
+            self.reported.lock().unwrap().push(others.iter().map(|other| other.x).collect());
+        }
+    }
+
+    #[test]
+    fn new_best_top_n1() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let individuals: Vec<IndividualTest3> = (0..10)
+            .map(|i| IndividualTest3 { x: 100 + i, reported: reported.clone() })
+            .collect();
+
+        let population = PopulationBuilder::<IndividualTest3>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let mut simulation = SimulationBuilder::<IndividualTest3>::new()
+            .iterations(10)
+            .new_best_top_n(3)
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        let reported = reported.lock().unwrap();
+
+        assert_eq!(reported.len(), 10);
+        assert_eq!(reported[0], vec![99, 100]);
+        assert_eq!(reported[9], vec![90, 91, 92]);
+    }
+
+    #[test]
+    fn stop_handle1() {
+        let path = env::temp_dir().join("darwin_rs_stop_handle1.csv");
+
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .iterations(u32::MAX)
+            .save_fitness_csv(path.to_str().unwrap()));
+
+        let handle = simulation.stop_handle();
+        handle.stop();
+        assert!(handle.is_stop_requested());
+
+        simulation.run();
+
+        assert_eq!(simulation.simulation_result.iteration_counter, 0);
+        assert!(path.exists());
+        fs::remove_file(&path).unwrap();
+
+        // The request has been consumed, the next run isn't stopped.
+        assert!(!handle.is_stop_requested());
+        simulation.type_of_simulation = SimulationType::EndIteration(5);
+        simulation.run();
+
+        assert_eq!(simulation.simulation_result.iteration_counter, 5);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn stop_handle2() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .iterations(u32::MAX));
+
+        // All populations are paused, so the simulation waits instead of counting iterations.
+        for population in &simulation.habitat {
+            population.pause_handle().pause();
+        }
+
+        let handle = simulation.stop_handle();
+        let stopper = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            handle.stop();
+        });
+
+        simulation.run();
+        stopper.join().unwrap();
+
+        assert!(simulation.simulation_result.iteration_counter <= 1);
+    }
+
+    #[derive(Debug, Clone)]
+    struct IndividualTest4 {
+        x: i64,
+        with_stats: bool,
+    }
+
+    impl Individual for IndividualTest4 {
+        fn mutate(&mut self) {
+            self.x -= 1;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            self.x.abs() as f64
+        }
+
+        fn reset(&mut self) {
+            self.x = 100;
+        }
+
+        fn mutation_stats(&self) -> Option<HashMap<String, u64>> {
+            if self.with_stats {
+                let mut stats = HashMap::new();
+                stats.insert("decrement".to_string(), (100 - self.x) as u64);
+                Some(stats)
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn mutation_stats1() {
+        let populations: Vec<_> = [true, false, true].iter().map(|&with_stats| {
+            let individuals: Vec<IndividualTest4> = (0..5).map(|_| IndividualTest4 { x: 100, with_stats }).collect();
+
+            PopulationBuilder::<IndividualTest4>::new()
+                .initial_population(&individuals)
+                .reset_limit_end(0)
+                .finalize().unwrap()
+        }).collect();
+
+        let mut simulation = SimulationBuilder::<IndividualTest4>::new()
+            .iterations(10)
+            .add_multiple_populations(populations)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        // The population without statistics is ignored.
+        assert_eq!(simulation.mutation_stats.len(), 1);
+        assert_eq!(simulation.mutation_stats["decrement"], 20);
+    }
+
+    struct CollectSink {
+        metrics: Arc<Mutex<Vec<Metrics>>>,
+    }
+
+    impl MetricsSink for CollectSink {
+        fn record(&self, metrics: &Metrics) {
+            self.metrics.lock().unwrap().push(metrics.clone());
+        }
+    }
+
+    #[test]
+    fn metrics_sink1() {
+        let individuals: Vec<IndividualTest1> = (0..5).map(|i| IndividualTest1 { x: 100 + i }).collect();
+
+        let population = PopulationBuilder::<IndividualTest1>::new()
+            .initial_population(&individuals)
+            .reset_limit_end(0)
+            .finalize().unwrap();
+
+        let metrics = Arc::new(Mutex::new(Vec::new()));
+
+        let mut simulation = SimulationBuilder::<IndividualTest1>::new()
+            .iterations(10)
+            .metrics_sink(CollectSink { metrics: metrics.clone() })
+            .add_population(population)
+            .finalize().unwrap();
+
+        simulation.run();
+
+        let metrics = metrics.lock().unwrap();
+
+        assert_eq!(metrics.len(), 10);
+        // After the first iteration: 99, 100, 100, 101, 101
+        assert_eq!(metrics[0], Metrics { iteration: 1, best: 99.0, worst: 101.0, median: 100.0, diversity: 0.0 });
+        assert_eq!(metrics[9].iteration, 10);
+        assert_eq!(metrics[9].best, 90.0);
+    }
+
+    #[test]
+    fn best_individual1() {
+        let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+            .iterations(10));
+
+        assert!(simulation.best_individual().is_none());
+        assert_eq!(simulation.best_fitness(), None);
+
+        simulation.run();
+
+        assert_eq!(simulation.best_fitness(), Some(simulation.simulation_result.fittest[0].fitness));
+        assert_eq!(simulation.best_individual().unwrap().x, simulation.simulation_result.fittest[0].individual.x);
+        assert_eq!(simulation.habitat[0].best_fitness(), Some(simulation.habitat[0].population[0].fitness));
+    }
+
+    #[test]
+    fn fitness_scaling1() {
+        // One individual dominates all the others.
+        let fitness = [2.0, 40.0, 40.0, 40.0, 40.0];
+
+        let probability = |scaling: FitnessScaling| {
+            let weights = scaling.weights(&fitness, OptimizationGoal::Minimize);
+            weights[0] / weights.iter().sum::<f64>()
+        };
+
+        assert!(probability(FitnessScaling::None) > 0.9);
+        // The dominant individual gets 60.8 / (60.8 + 4 * 22.8) = 40%.
+        assert!((probability(FitnessScaling::SigmaTruncation) - 0.4).abs() < 1e-9);
+
+        // All the same fitness: sigma truncation falls back to equal weights.
+        assert_eq!(FitnessScaling::SigmaTruncation.weights(&[5.0, 5.0, 5.0], OptimizationGoal::Maximize),
+            vec![1.0, 1.0, 1.0]);
+        assert_eq!(FitnessScaling::SigmaTruncation.weights(&[10.0, 8.0, 0.0], OptimizationGoal::Maximize)
+            .iter().filter(|weight| **weight > 0.0).count(), 3);
+        assert!(FitnessScaling::None.weights(&[], OptimizationGoal::Minimize).is_empty());
+    }
+
+    #[test]
+    fn fitness_scaling2() {
+        // Rank based selection is much flatter than selection by the raw fitness.
+        let share = |share_selection: ShareSelection| {
+            let mut simulation = make_simulation(SimulationBuilder::<IndividualTest1>::new()
+                .share_top_k(5)
+                .share_selection(share_selection)
+                .fitness_scaling(FitnessScaling::None));
+
+            simulation.simulation_result.fittest = [2.0, 40.0, 40.0, 40.0, 40.0].iter().map(|&fitness| IndividualWrapper {
+                individual: IndividualTest1 { x: 0 },
+                fitness,
+                num_of_mutations: 1,
+                id: 1,
+            }).collect();
+
+            let weights = simulation.share_weights(5);
+            weights[0] / weights.iter().sum::<f64>()
+        };
+
+        let roulette = share(ShareSelection::Roulette);
+        let rank = share(ShareSelection::Rank);
 
+        assert!(roulette > 0.9);
+        assert!((rank - 5.0 / 15.0).abs() < 1e-9);
+        assert!(rank < roulette / 2.0);
     }
 }
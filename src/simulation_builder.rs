@@ -13,11 +13,19 @@
 //!
 //!
 
-use std;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
 
-use simulation::{Simulation, SimulationType, SimulationResult};
-use individual::{Individual};
+use rand;
+
+use simulation::{Simulation, SimulationType, SimulationResult, PhaseConfig, ProgressHandle, MigrationTopology,
+    ShareSelection, FitnessScaling, StopCriterion};
+use individual::{Individual, OptimizationGoal};
 use population::Population;
+use util::seeded_rng;
+use metrics::MetricsSink;
 
 /// This is a helper struct in order to build (configure) a valid simulation.
 /// See builder pattern: https://en.wikipedia.org/wiki/Builder_pattern
@@ -31,6 +39,21 @@ pub struct SimulationBuilder<T: Individual + Send + Sync> {
 error_chain! {
     errors {
         EndIterationTooLow
+        ShareTopKTooLow
+        ReevaluateEveryTooLow
+        StagnationLimitTooLow
+        FitnessNotFinite
+        NoPopulations
+        FactorNotFinite
+        FitnessHistoryTooShort
+        AcceptWorseProbOutOfRange
+        NewBestEpsilonTooLow
+    }
+}
+
+impl<T: Individual + Send + Sync> Default for SimulationBuilder<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -46,8 +69,8 @@ impl<T: Individual + Send + Sync> SimulationBuilder<T> {
                 habitat: Vec::new(),
                 total_time_in_ms: 0.0,
                 simulation_result: SimulationResult {
-                    improvement_factor: std::f64::MAX,
-                    original_fitness: std::f64::MAX,
+                    improvement_factor: f64::MAX,
+                    original_fitness: f64::MAX,
                     fittest: Vec::new(),
                     iteration_counter: 0
                 },
@@ -56,7 +79,32 @@ impl<T: Individual + Send + Sync> SimulationBuilder<T> {
                 output_every: 10,
                 output_every_counter: 0,
                 share_every: 10,
-                share_counter: 0
+                share_counter: 0,
+                share_top_k: 1,
+                share_cursor: 0,
+                accept_worse_prob: 1.0,
+                reevaluate_best_every: None,
+                stagnation_limit: None,
+                stagnation_counter: 0,
+                max_run_time: None,
+                share_selection: ShareSelection::RoundRobin,
+                fitness_scaling: FitnessScaling::None,
+                migration_topology: MigrationTopology::FullMesh,
+                goal: OptimizationGoal::Minimize,
+                rng: rand::weak_rng(),
+                progress: ProgressHandle::new(),
+                new_best_epsilon: 0.0,
+                reported_fitness: f64::MAX,
+                phases: Vec::new(),
+                next_phase: 0,
+                stop_criterion: None,
+                fitness_history_len: None,
+                fitness_history: VecDeque::new(),
+                fitness_csv_path: None,
+                new_best_top_n: 1,
+                stop_requested: Arc::new(AtomicBool::new(false)),
+                mutation_stats: HashMap::new(),
+                metrics_sink: None,
             },
         }
     }
@@ -129,13 +177,181 @@ impl<T: Individual + Send + Sync> SimulationBuilder<T> {
         self
     }
 
+    /// If share fittest is enabled, share the top k individuals of all populations in a round
+    /// robin fashion instead of always sharing the single fittest individual. Default: 1.
+    /// The value is limited by the number of global fittest individuals.
+    pub fn share_top_k(mut self, share_top_k: usize) -> SimulationBuilder<T> {
+        self.simulation.share_top_k = share_top_k;
+        self
+    }
+
+    /// The probability that a population accepts a shared individual that is worse than its
+    /// own fittest individual. Must be between 0.0 and 1.0.
+    /// Default: 1.0 (Only usefull in combination with `share_top_k`).
+    pub fn accept_worse_prob(mut self, accept_worse_prob: f64) -> SimulationBuilder<T> {
+        self.simulation.accept_worse_prob = accept_worse_prob;
+        self
+    }
+
+    /// Re-calculate the fitness of the global fittest individuals every nth iteration.
+    /// Only useful if the fitness function is noisy, since it calls `calculate_fitness` again.
+    pub fn reevaluate_best_every(mut self, reevaluate_best_every: u32) -> SimulationBuilder<T> {
+        self.simulation.reevaluate_best_every = Some(reevaluate_best_every);
+        self
+    }
+
+    /// Only count an improvement of at least `new_best_epsilon` as a new fittest individual
+    /// (statistics, log output, `new_fittest_found`, stagnation and sharing). Useful for continuous problems
+    /// where the fitness improves by tiny amounts all the time. Must not be negative. Default: 0.0
+    pub fn new_best_epsilon(mut self, new_best_epsilon: f64) -> SimulationBuilder<T> {
+        self.simulation.new_best_epsilon = new_best_epsilon;
+        self
+    }
+
+    /// Sets how the shared individual is chosen from the top `share_top_k` global fittest
+    /// individuals: one after the other or randomly weighted by fitness or by rank.
+    /// Default: `ShareSelection::RoundRobin`
+    pub fn share_selection(mut self, share_selection: ShareSelection) -> SimulationBuilder<T> {
+        self.simulation.share_selection = share_selection;
+        self
+    }
+
+    /// Sets how the fitness values are turned into weights for `ShareSelection::Roulette`.
+    /// Use `FitnessScaling::SigmaTruncation` (or `ShareSelection::Rank`) if a single individual
+    /// dominates all the others. Default: `FitnessScaling::None`
+    pub fn fitness_scaling(mut self, fitness_scaling: FitnessScaling) -> SimulationBuilder<T> {
+        self.simulation.fitness_scaling = fitness_scaling;
+        self
+    }
+
+    /// Sets which populations (islands) exchange individuals when `share_fittest` is enabled.
+    /// With `MigrationTopology::Ring` or `MigrationTopology::Grid` the individuals only
+    /// migrate between neighbors instead of using the global fittest individuals.
+    /// Default: `MigrationTopology::FullMesh`
+    pub fn migration_topology(mut self, migration_topology: MigrationTopology) -> SimulationBuilder<T> {
+        self.simulation.migration_topology = migration_topology;
+        self
+    }
+
+    /// Sets the number of iterations without a new fittest individual after which the
+    /// simulation stops, even if the other stop criteria haven't been reached yet.
+    /// In contrast to the reset limit of the populations this ends the simulation.
+    /// Must be at least 1.
+    pub fn stagnation_limit(mut self, stagnation_limit: u32) -> SimulationBuilder<T> {
+        self.simulation.stagnation_limit = Some(stagnation_limit);
+        self
+    }
+
+    /// Sets the maximum (wall clock) run time of the simulation. The simulation stops when the
+    /// time is up, even if the other stop criteria haven't been reached yet. The fittest
+    /// individuals found so far are kept in the result.
+    pub fn max_run_time(mut self, max_run_time: Duration) -> SimulationBuilder<T> {
+        self.simulation.max_run_time = Some(max_run_time);
+        self
+    }
+
+    /// Sets a user defined stop criterion. The simulation stops when the criterion is met,
+    /// even if the other stop criteria haven't been reached yet.
+    /// See `FitnessLimit` for a built-in criterion.
+    pub fn stop_criterion<S: StopCriterion + 'static>(mut self, stop_criterion: S) -> SimulationBuilder<T> {
+        self.simulation.stop_criterion = Some(Box::new(stop_criterion));
+        self
+    }
+
+    /// Records the fitness of the fittest individual after each iteration, see
+    /// `Simulation::fitness_history` and `Simulation::save_fitness_history`. Only the last
+    /// `max_len` entries are kept. Must be at least 1.
+    pub fn fitness_history(mut self, max_len: usize) -> SimulationBuilder<T> {
+        self.simulation.fitness_history_len = Some(max_len);
+        self
+    }
+
+    /// Writes the fitness of all individuals into the given file when the simulation has
+    /// finished, see `Simulation::save_fitness_csv`.
+    pub fn save_fitness_csv(mut self, path: &str) -> SimulationBuilder<T> {
+        self.simulation.fitness_csv_path = Some(path.to_string());
+        self
+    }
+
+    /// Sets the number of global fittest individuals that are passed to the
+    /// `on_new_best_population` method of the `Individual` trait when a new fittest individual
+    /// is found. Default: 1 (only the new fittest individual itself).
+    pub fn new_best_top_n(mut self, new_best_top_n: usize) -> SimulationBuilder<T> {
+        self.simulation.new_best_top_n = new_best_top_n;
+        self
+    }
+
+    /// Sets a sink that receives the metrics (best, worst and median fitness, diversity) after
+    /// every iteration. See `metrics::JsonLinesSink` for a sink that writes the metrics into
+    /// a file.
+    pub fn metrics_sink<S: MetricsSink + 'static>(mut self, metrics_sink: S) -> SimulationBuilder<T> {
+        self.simulation.metrics_sink = Some(Box::new(metrics_sink));
+        self
+    }
+
+    /// Seeds the random number generator of the simulation (used for sharing individuals).
+    /// The populations have their own generators, see `PopulationBuilder::rng_seed`.
+    /// By default the generator is seeded randomly.
+    pub fn rng_seed(mut self, seed: u32) -> SimulationBuilder<T> {
+        self.simulation.rng = seeded_rng(seed);
+        self
+    }
+
+    /// Configures if lower or higher fitness values are better. This is applied to all
+    /// populations of the simulation. With `OptimizationGoal::Maximize` the simulation
+    /// type `fitness` stops when the fittest individual reaches at least the given value.
+    /// Default: `OptimizationGoal::Minimize`
+    pub fn goal(mut self, goal: OptimizationGoal) -> SimulationBuilder<T> {
+        self.simulation.goal = goal;
+        self
+    }
+
+    /// Add a new phase to the simulation. When the given iteration is reached, the parameters
+    /// of the phase are applied to all populations.
+    pub fn add_phase(mut self, iteration: u32, phase: PhaseConfig) -> SimulationBuilder<T> {
+        self.simulation.phases.push((iteration, phase));
+        self.simulation.phases.sort_by_key(|&(iteration, _)| iteration);
+        self
+    }
+
     /// This checks the configuration of the simulation and returns an error or Ok if no errors
     /// where found.
-    pub fn finalize(self) -> Result<Simulation<T>> {
+    pub fn finalize(mut self) -> Result<Simulation<T>> {
+        for population in &mut self.simulation.habitat {
+            population.goal = self.simulation.goal;
+        }
+
         match self.simulation {
             Simulation { type_of_simulation: SimulationType::EndIteration(0..=9), .. } => {
                 Err(ErrorKind::EndIterationTooLow.into())
             }
+            Simulation { type_of_simulation: SimulationType::EndFitness(fitness), .. } if !fitness.is_finite() => {
+                Err(ErrorKind::FitnessNotFinite.into())
+            }
+            Simulation { type_of_simulation: SimulationType::EndFactor(factor), .. } if !factor.is_finite() => {
+                Err(ErrorKind::FactorNotFinite.into())
+            }
+            Simulation { share_top_k: 0, .. } => {
+                Err(ErrorKind::ShareTopKTooLow.into())
+            }
+            Simulation { reevaluate_best_every: Some(0), .. } => {
+                Err(ErrorKind::ReevaluateEveryTooLow.into())
+            }
+            Simulation { stagnation_limit: Some(0), .. } => {
+                Err(ErrorKind::StagnationLimitTooLow.into())
+            }
+            Simulation { fitness_history_len: Some(0), .. } => {
+                Err(ErrorKind::FitnessHistoryTooShort.into())
+            }
+            Simulation { accept_worse_prob, .. } if !(0.0..=1.0).contains(&accept_worse_prob) => {
+                Err(ErrorKind::AcceptWorseProbOutOfRange.into())
+            }
+            Simulation { new_best_epsilon, .. } if !(new_best_epsilon >= 0.0 && new_best_epsilon.is_finite()) => {
+                Err(ErrorKind::NewBestEpsilonTooLow.into())
+            }
+            Simulation { ref habitat, .. } if habitat.is_empty() => {
+                Err(ErrorKind::NoPopulations.into())
+            }
             _ => Ok(self.simulation),
         }
     }
@@ -0,0 +1,103 @@
+//! This module defines tournaments for problems with a relative fitness: instead of calculating
+//! an absolute fitness for each individual, the individuals play against each other using the
+//! `play` method of the `Individual` trait. The winners can then be used as the initial
+//! population for the next simulation.
+//!
+//! darwin-rs: evolutionary algorithms with Rust
+//!
+//! Written by Willi Kappler, Version 0.4 (2017.06.26)
+//!
+//! Repository: https://github.com/willi-kappler/darwin-rs
+//!
+//! License: MIT
+//!
+//! This library allows you to write evolutionary algorithms (EA) in Rust.
+//! Examples provided: TSP, Sudoku, Queens Problem, OCR
+//!
+//!
+
+use std::cmp::Ordering;
+
+use individual::Individual;
+
+/// Plays a round robin tournament: every individual plays once against every other individual.
+/// A win counts two points, a draw one point. Returns the indices of the individuals sorted by
+/// their points, the strongest first. Individuals with the same points keep their order.
+pub fn round_robin<T: Individual>(players: &[T]) -> Vec<usize> {
+    let mut points = vec![0; players.len()];
+
+    for (i, player1) in players.iter().enumerate() {
+        for (j, player2) in players.iter().enumerate().skip(i + 1) {
+            match player1.play(player2) {
+                Ordering::Greater => points[i] += 2,
+                Ordering::Less => points[j] += 2,
+                Ordering::Equal => {
+                    points[i] += 1;
+                    points[j] += 1;
+                }
+            }
+        }
+    }
+
+    let mut ranking: Vec<usize> = (0..players.len()).collect();
+    ranking.sort_by(|a, b| points[*b].cmp(&points[*a]));
+    ranking
+}
+
+/// Plays a round robin tournament (see `round_robin`) and returns the `num_of_winners`
+/// strongest individuals, the strongest first.
+pub fn keep_winners<T: Individual + Clone>(players: &[T], num_of_winners: usize) -> Vec<T> {
+    round_robin(players).into_iter()
+        .take(num_of_winners)
+        .map(|index| players[index].clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use super::{round_robin, keep_winners};
+    use individual::Individual;
+
+    #[derive(Debug, Clone)]
+    struct Player {
+        strength: u32,
+    }
+
+    impl Individual for Player {
+        fn mutate(&mut self) {
+            self.strength += 1;
+        }
+
+        fn calculate_fitness(&mut self) -> f64 {
+            0.0
+        }
+
+        fn reset(&mut self) {
+            self.strength = 0;
+        }
+
+        // Transitive game: the stronger player always wins.
+        fn play(&self, opponent: &Self) -> Ordering {
+            self.strength.cmp(&opponent.strength)
+        }
+    }
+
+    fn make_players() -> Vec<Player> {
+        vec![3, 7, 1, 9, 5, 9].into_iter().map(|strength| Player { strength }).collect()
+    }
+
+    #[test]
+    fn round_robin1() {
+        // The two strongest players draw, so the first one found stays in front.
+        assert_eq!(round_robin(&make_players()), vec![3, 5, 1, 4, 0, 2]);
+    }
+
+    #[test]
+    fn keep_winners1() {
+        let winners: Vec<u32> = keep_winners(&make_players(), 3).iter().map(|player| player.strength).collect();
+
+        assert_eq!(winners, vec![9, 9, 7]);
+    }
+}
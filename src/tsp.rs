@@ -0,0 +1,160 @@
+//! This module defines a reusable individual for the travelling salesman problem (TSP):
+//! https://en.wikipedia.org/wiki/Travelling_salesman_problem
+//! The cost between two cities is taken from a distance matrix instead of being calculated
+//! from coordinates, so real road networks with asymmetric costs (one way streets, traffic)
+//! can be used as well. See also examples/tsp and examples/tsp2.
+//!
+//! darwin-rs: evolutionary algorithms with Rust
+//!
+//! Written by Willi Kappler, Version 0.4 (2017.06.26)
+//!
+//! Repository: https://github.com/willi-kappler/darwin-rs
+//!
+//! License: MIT
+//!
+//! This library allows you to write evolutionary algorithms (EA) in Rust.
+//! Examples provided: TSP, Sudoku, Queens Problem, OCR
+//!
+//!
+
+use std::sync::Arc;
+
+use rand::{self, Rng};
+
+use individual::Individual;
+use genome;
+
+/// The `TspIndividual` type. A round trip through all cities that starts and ends at city 0.
+/// The distance matrix is shared between all individuals.
+#[derive(Debug,Clone)]
+pub struct TspIndividual {
+    /// The order in which the cities are visited, always starting with city 0.
+    pub path: Vec<usize>,
+    /// The cost to go from city i to city j: `distances[i][j]`. This doesn't need to be
+    /// symmetric.
+    pub distances: Arc<Vec<Vec<f64>>>,
+}
+
+impl TspIndividual {
+    /// Creates a new individual that visits the cities in the order of the distance matrix.
+    /// The matrix must be square.
+    pub fn new(distances: Arc<Vec<Vec<f64>>>) -> TspIndividual {
+        assert!(distances.iter().all(|row| row.len() == distances.len()), "Distance matrix is not square");
+
+        TspIndividual {
+            path: (0..distances.len()).collect(),
+            distances,
+        }
+    }
+
+    /// Creates a new individual with a symmetric distance matrix, calculated from the
+    /// euclidean distance between the given coordinates.
+    pub fn from_coordinates(cities: &[(f64, f64)]) -> TspIndividual {
+        let distances = cities.iter()
+            .map(|&(x1, y1)| cities.iter().map(|&(x2, y2)| (x2 - x1).hypot(y2 - y1)).collect())
+            .collect();
+
+        TspIndividual::new(Arc::new(distances))
+    }
+
+    /// Returns true if the distance matrix is symmetric, i.e. the cost from city i to city j
+    /// is the same as the cost from city j to city i.
+    pub fn is_symmetric(&self) -> bool {
+        let len = self.distances.len();
+        (0..len).all(|i| ((i + 1)..len).all(|j| self.distances[i][j] == self.distances[j][i]))
+    }
+}
+
+impl Individual for TspIndividual {
+    /// Uses one of the five operators of the `genome` module: swap two cities, move one city
+    /// to another position, reverse, scramble or swap segments of the path.
+    /// The start city is never changed.
+    fn mutate(&mut self) {
+        if self.path.is_empty() {
+            return;
+        }
+
+        let mut rng = rand::thread_rng();
+        let path = &mut self.path[1..];
+
+        match rng.gen_range(0, 5) {
+            0 => genome::swap(path, &mut rng),
+            1 => genome::insert_shift(path, &mut rng),
+            2 => genome::reverse_segment(path, &mut rng),
+            3 => genome::scramble_segment(path, &mut rng),
+            _ => genome::block_swap(path, &mut rng),
+        }
+    }
+
+    /// The total cost of the round trip, the lower the better.
+    fn calculate_fitness(&mut self) -> f64 {
+        let len = self.path.len();
+
+        (0..len).map(|i| self.distances[self.path[i]][self.path[(i + 1) % len]]).sum()
+    }
+
+    fn reset(&mut self) {
+        self.path = (0..self.distances.len()).collect();
+    }
+
+    /// The number of positions with a different city.
+    fn distance(&self, other: &Self) -> f64 {
+        self.path.iter().zip(&other.path).filter(|&(city1, city2)| city1 != city2).count() as f64
+    }
+
+    fn genome_eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::TspIndividual;
+    use individual::Individual;
+
+    #[test]
+    fn calculate_fitness1() {
+        // Going "forward" is cheap, going "backward" is expensive.
+        let distances = vec![
+            vec![0.0, 1.0, 10.0],
+            vec![10.0, 0.0, 1.0],
+            vec![1.0, 10.0, 0.0],
+        ];
+
+        let mut individual = TspIndividual::new(Arc::new(distances));
+
+        assert!(!individual.is_symmetric());
+        assert_eq!(individual.calculate_fitness(), 3.0);
+
+        individual.path = vec![0, 2, 1];
+        assert_eq!(individual.calculate_fitness(), 30.0);
+
+        let mut individual = TspIndividual::from_coordinates(&[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0)]);
+
+        assert!(individual.is_symmetric());
+        assert_eq!(individual.calculate_fitness(), 12.0);
+    }
+
+    #[test]
+    fn mutate1() {
+        let cities: Vec<(f64, f64)> = (0..10).map(|i| (i as f64, (i * i) as f64)).collect();
+        let mut individual = TspIndividual::from_coordinates(&cities);
+
+        for _ in 0..1000 {
+            individual.mutate();
+
+            let mut sorted = individual.path.clone();
+            sorted.sort();
+
+            assert_eq!(individual.path[0], 0);
+            assert_eq!(sorted, (0..10).collect::<Vec<usize>>());
+        }
+
+        // Nothing to mutate without cities.
+        let mut individual = TspIndividual::new(Arc::new(Vec::new()));
+        individual.mutate();
+        assert!(individual.path.is_empty());
+    }
+}
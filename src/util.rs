@@ -0,0 +1,96 @@
+//! This module defines small helper functions that can be used when implementing the
+//! `Individual` trait.
+//!
+//! darwin-rs: evolutionary algorithms with Rust
+//!
+//! Written by Willi Kappler, Version 0.4 (2017.06.26)
+//!
+//! Repository: https://github.com/willi-kappler/darwin-rs
+//!
+//! License: MIT
+//!
+//! This library allows you to write evolutionary algorithms (EA) in Rust.
+//! Examples provided: TSP, Sudoku, Queens Problem, OCR
+//!
+//!
+
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+/// Calls the given closure until it returns `Some` value, but at most `max_tries` times.
+/// Returns `None` if the closure didn't succeed within the limit.
+///
+/// Mutation functions often retry a random draw until a condition holds (for example
+/// finding an empty square on the board in the queens example). For a nearly full board
+/// such a loop can run for a very long time. Use this function to limit the number of tries.
+///
+/// ```
+/// use darwin_rs::util::retry_with_limit;
+///
+/// let board = vec![1, 1, 0, 1];
+/// let mut index = 0;
+///
+/// // Find an empty square, give up after 100 tries.
+/// let empty = retry_with_limit(100, || {
+///     index = (index + 1) % board.len();
+///     if board[index] == 0 { Some(index) } else { None }
+/// });
+///
+/// assert_eq!(empty, Some(2));
+/// ```
+pub fn retry_with_limit<T, F: FnMut() -> Option<T>>(max_tries: u32, mut f: F) -> Option<T> {
+    for _ in 0..max_tries {
+        let result = f();
+        if result.is_some() {
+            return result;
+        }
+    }
+
+    None
+}
+
+/// Creates a fast random number generator from the given seed. The same seed always gives
+/// the same sequence of random numbers, this is useful for reproducible runs.
+pub fn seeded_rng(seed: u32) -> XorShiftRng {
+    // The xorshift generator must not be seeded with all zeros.
+    XorShiftRng::from_seed([0x193a_6754, 0xa8a7_d469 ^ seed, 0x9783_0e05, 0x113b_a7bb])
+}
+
+/// Returns a random index into `weights`, each index is chosen with a probability
+/// proportional to its weight. The weights must not be negative and must not be empty.
+pub fn weighted_index<R: Rng>(rng: &mut R, weights: &[f64]) -> usize {
+    let sum: f64 = weights.iter().sum();
+    let mut value = rng.gen::<f64>() * sum;
+
+    for (index, weight) in weights.iter().enumerate() {
+        if value < *weight {
+            return index;
+        }
+        value -= weight;
+    }
+
+    weights.len() - 1
+}
+
+#[cfg(test)]
+mod test {
+    use super::retry_with_limit;
+
+    #[test]
+    fn retry_with_limit1() {
+        let mut counter = 0;
+
+        let result: Option<u32> = retry_with_limit(10, || { counter += 1; None });
+
+        assert_eq!(result, None);
+        assert_eq!(counter, 10);
+    }
+
+    #[test]
+    fn retry_with_limit2() {
+        let mut counter = 0;
+
+        let result = retry_with_limit(10, || { counter += 1; if counter == 3 { Some(counter) } else { None } });
+
+        assert_eq!(result, Some(3));
+    }
+}